@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::generator::{config::ClockValues, token_config::TokenProgram};
+
+/*
+Persistent cache for the ephemeral wrapper crates.
+
+The wrapper is generated into `target/debuggen/wrappers/<package>` instead of a fresh temp dir, together with a hash of
+everything the generated code depends on. When the hash did not change, generation is skipped; the incremental cargo
+build still runs, so changes the hash does not cover (a path dependency of the program, ...) are built in.
+Every wrapper, cached or generated into an output directory, also keeps the flags it was generated with so `run` can
+regenerate it the same way.
+*/
+
+const HASH_FILE: &str = ".anchor-lldb-hash";
+const OPTIONS_FILE: &str = ".anchor-lldb-options.json";
+
+/// The generation flags of a wrapper and the hash of the IDL it was generated from
+#[derive(Default, Serialize, Deserialize)]
+pub struct StoredOptions {
+    pub args: Option<String>,
+    pub matrix: bool,
+    pub entrypoint: bool,
+    pub token_program: Option<TokenProgram>,
+    pub token_config: Option<String>,
+    pub clock: Option<ClockValues>,
+    pub idl_hash: String,
+}
+
+pub fn cache_dir(package: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(std::env::current_dir()?
+        .join("target/debuggen/wrappers")
+        .join(package))
+}
+
+/// Hashes the IDL, the `--args`, `--token-config`, `--token-program` and `--clock` inputs, `--matrix`, `--entrypoint`, the program's `src/**/*.rs` (what `extract_account_struct_map` reads), its `Cargo.toml`, the workspace `Cargo.lock` and the anchor-lldb version.
+/// The templates are read at runtime, so they are hashed too in case they changed without a version bump.
+pub fn wrapper_hash(
+    idl_json: &str,
+    inputs: &[&str],
+    matrix: bool,
+    entrypoint: bool,
+    program_crate_path: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update(idl_json.as_bytes());
+    for input in inputs {
+        // Length prefixed, so moving text from one input to the next changes the hash
+        hasher.update((input.len() as u64).to_le_bytes());
+        hasher.update(input.as_bytes());
+    }
+    hasher.update([matrix as u8, entrypoint as u8]);
+
+    hash_rs_files(&mut hasher, &Path::new(program_crate_path).join("src"))?;
+    // The wrapper depends on the program crate, a changed dependency or feature changes what it links
+    for manifest in [Path::new(program_crate_path).join("Cargo.toml"), std::env::current_dir()?.join("Cargo.lock")] {
+        hasher.update(fs::read(&manifest).unwrap_or_default());
+    }
+    hash_rs_files(&mut hasher, &Path::new(env!("CARGO_MANIFEST_DIR")).join("src/templates"))?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn hash_rs_files(hasher: &mut Sha256, dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut sources: Vec<PathBuf> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
+        .map(|e| e.into_path())
+        .collect();
+    // WalkDir order depends on the file system, the hash must not
+    sources.sort();
+
+    for source in sources {
+        let relative = source.strip_prefix(dir).unwrap_or(&source);
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update(fs::read(&source)?);
+    }
+
+    Ok(())
+}
+
+pub fn idl_hash(idl_json: &str) -> String {
+    format!("{:x}", Sha256::digest(idl_json.as_bytes()))
+}
+
+pub fn store_options(dir: &Path, options: &StoredOptions) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(dir.join(OPTIONS_FILE), serde_json::to_string_pretty(options)?)?;
+    Ok(())
+}
+
+/// `None` for wrappers generated before the options were stored
+pub fn load_options(dir: &Path) -> Option<StoredOptions> {
+    let json = fs::read_to_string(dir.join(OPTIONS_FILE)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// The cache is fresh when it was generated from the same inputs and its binary was built successfully, its
+/// generated sources are reused then
+pub fn is_fresh(dir: &Path, hash: &str, bin_path: &Path) -> bool {
+    bin_path.exists()
+        && fs::read_to_string(dir.join(HASH_FILE)).is_ok_and(|stored| stored.trim() == hash)
+}
+
+/// Written only after a successful build so a failed build is never reported as cached
+pub fn store_hash(dir: &Path, hash: &str) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(dir.join(HASH_FILE), hash)?;
+    Ok(())
+}
+
+/// A stale hash must not survive a regeneration that fails half way
+pub fn invalidate(dir: &Path) {
+    let _ = fs::remove_file(dir.join(HASH_FILE));
+}
@@ -0,0 +1,149 @@
+use std::collections::{HashMap, HashSet};
+
+use anchor_idl::{Idl, IdlInstruction, IdlTypeDef};
+use serde::Serialize;
+
+use crate::generator::accounts::AccountMockKind;
+use crate::generator::args::InstructionArgCode;
+use crate::generator::bumps::InstructionBumpsCode;
+use crate::generator::token::associated_token;
+use crate::utils::{account_paths, composite_groups, flatten_accounts, idl_type_to_string, to_camel_case};
+
+/*
+Preflight of the IDL and the program sources: everything the generator cannot handle, found without writing any file.
+Errors make generation panic or the wrapper fail to compile, warnings produce a wrapper that may not behave like the program.
+*/
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    pub instruction: String,
+    pub message: String,
+}
+
+pub fn check(idl: &Idl, account_map: &HashMap<String, String>) -> Vec<Finding> {
+    idl.instructions
+        .iter()
+        .flat_map(|ix| check_instruction(ix, &idl.types, account_map))
+        .collect()
+}
+
+pub fn check_instruction(
+    ix: &IdlInstruction,
+    types: &[IdlTypeDef],
+    account_map: &HashMap<String, String>,
+) -> Vec<Finding> {
+    let mut findings = vec![];
+    let mut report = |severity: Severity, message: String| {
+        findings.push(Finding {
+            severity,
+            instruction: ix.name.clone(),
+            message,
+        });
+    };
+
+    // Composite groups are built as the nested `Accounts` struct of the field with the group's name
+    for group in composite_groups(&ix.accounts) {
+        if !account_map.contains_key(&group.name) {
+            report(
+                Severity::Warning,
+                format!(
+                    "composite account group `{}`: no `Accounts` struct field with this name found in the program sources, `{}` is assumed",
+                    group.name,
+                    to_camel_case(&group.name)
+                ),
+            );
+        }
+    }
+
+    // Accounts of composite groups get their own mocks, but their type is looked up by field name in the program sources
+    let paths = account_paths(&ix.accounts);
+    let mut reported = HashSet::new();
+    for (_, account) in &paths {
+        let same_name: Vec<&str> = paths
+            .iter()
+            .filter(|(_, other)| other.name == account.name)
+            .map(|(path, _)| path.as_str())
+            .collect();
+        if same_name.len() > 1 && reported.insert(&account.name) {
+            report(
+                Severity::Warning,
+                format!(
+                    "account name `{}` is used {} times ({}): each one is mocked separately, but their type is looked up by name in the program sources so they all get the same one",
+                    account.name,
+                    same_name.len(),
+                    same_name.join(", ")
+                ),
+            );
+        }
+    }
+
+    // Same walk as `InstructionAcountCode::generate_account_code`
+    for account in flatten_accounts(&ix.accounts) {
+        if AccountMockKind::classify(account, account_map, types) == AccountMockKind::Pda && !account_map.contains_key(&account.name) {
+            report(
+                Severity::Error,
+                format!(
+                    "account `{}`: no `Account<'info, T>` field with this name found in the program sources",
+                    account.name
+                ),
+            );
+        }
+
+        if let Some(pda) = &account.pda {
+            // Argument seeds of a type without a seed expression fail generation
+            if let Err(e) = InstructionBumpsCode::seed_exprs(pda, &[], "", &ix.args, types) {
+                report(Severity::Error, format!("account `{}`: {}", account.name, e));
+            }
+            // Associated token accounts are derived with the associated token program
+            if pda.program.is_some() && associated_token(account).is_none() {
+                report(
+                    Severity::Warning,
+                    format!(
+                        "account `{}`: PDA of another program, the seeds are derived with the program's own ID",
+                        account.name
+                    ),
+                );
+            }
+        }
+    }
+
+    for arg in &ix.args {
+        if InstructionArgCode::dummy_value(&arg.ty, types).is_none() {
+            report(
+                Severity::Warning,
+                format!(
+                    "argument `{}`: unsupported type `{}`, falls back to `Default::default()` which may not compile",
+                    arg.name,
+                    idl_type_to_string(&arg.ty)
+                ),
+            );
+        }
+    }
+
+    findings
+}
+
+pub fn print_findings(findings: &[Finding]) {
+    for finding in findings {
+        let label = match finding.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        println!("{label}[{}]: {}", finding.instruction, finding.message);
+    }
+
+    let errors = findings.iter().filter(|f| f.severity == Severity::Error).count();
+    println!(
+        "\n{} error(s), {} warning(s)",
+        errors,
+        findings.len() - errors
+    );
+}
@@ -0,0 +1,67 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::utils::find_on_path;
+
+/*
+Starts lldb on the extracted debug binary with the breakpoints requested from the CLI already set.
+*/
+
+pub struct LldbOptions<'a> {
+    pub program_mod: &'a str, // `#[program] pub mod <name>`, the handlers live under it
+    pub instruction: Option<&'a str>,
+    pub break_in_handler: bool,
+    pub extra_args: &'a [String],
+    pub program_args: &'a [String], // arguments of the debug binary itself, used by `run` inside lldb
+}
+
+/// Prefers `rust-lldb` (it loads the Rust pretty printers) and falls back to plain `lldb`.
+/// `rust-lldb` is only a wrapper script, so `lldb` itself has to be installed either way.
+pub fn find_lldb() -> Option<&'static str> {
+    find_on_path("lldb")?;
+
+    if find_on_path("rust-lldb").is_some() {
+        Some("rust-lldb")
+    } else {
+        Some("lldb")
+    }
+}
+
+pub fn breakpoint_commands(options: &LldbOptions) -> Vec<String> {
+    let mut commands = vec![];
+
+    if let Some(ix_name) = options.instruction {
+        commands.push(format!("breakpoint set --name call_{ix_name}"));
+    }
+
+    if options.break_in_handler {
+        // Handlers are plain functions inside the program module, e.g. `my_program::my_program::initialize`
+        let handler = options.instruction.unwrap_or("[^:]+");
+        commands.push(format!(
+            "breakpoint set --func-regex '::{}::{}$'",
+            options.program_mod, handler
+        ));
+    }
+
+    commands
+}
+
+pub fn launch_lldb(bin_path: &Path, options: &LldbOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let lldb = find_lldb().ok_or("`lldb` was not found on PATH, install it (e.g. via your package manager or Xcode command line tools)")?;
+
+    let mut cmd = Command::new(lldb);
+    for command in breakpoint_commands(options) {
+        cmd.arg("-o").arg(command);
+    }
+    cmd.args(options.extra_args);
+    cmd.arg("--").arg(bin_path).args(options.program_args);
+
+    println!("[INFO] Starting {} on {}", lldb, bin_path.display());
+
+    let status = cmd.status()?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", lldb, status).into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,296 @@
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use anchor_idl::Idl;
+use semver::{Comparator, Op, Version, VersionReq};
+use toml_edit::{DocumentMut, Item};
+use walkdir::WalkDir;
+
+use crate::debugger::find_lldb;
+use crate::generator::codegen::ANCHOR_LANG_VERSION;
+use crate::utils::{find_on_path, infer_paths};
+use crate::ProgramArgs;
+
+/*
+Environment diagnostics: every check either passes or says what is wrong and how to fix it.
+*/
+
+enum Status {
+    Ok(String),
+    Skipped(String),
+    Fail { problem: String, fix: String },
+}
+
+fn fail(problem: impl Into<String>, fix: impl Into<String>) -> Status {
+    Status::Fail {
+        problem: problem.into(),
+        fix: fix.into(),
+    }
+}
+
+/// Runs every check and prints the report, returns whether all of them passed
+pub fn run(program: &ProgramArgs) -> Result<bool, Box<dyn std::error::Error>> {
+    let root_dir = std::env::current_dir()?;
+    let mut results: Vec<(&str, Status)> = vec![];
+
+    results.push(("cargo", check_cargo()));
+    results.push(("lldb", check_lldb()));
+    results.push(("workspace", check_workspace(&root_dir)));
+
+    let paths = match (&program.idl, &program.program_crate_path) {
+        (Some(idl), Some(crate_path)) => {
+            results.push(("program paths", Status::Ok("--idl and --program-crate-path given explicitly".to_string())));
+            Some((idl.clone(), crate_path.clone()))
+        }
+        _ => match infer_paths(&program.package) {
+            Ok(paths) => {
+                results.push(("program paths", Status::Ok(format!("program crate at {}", paths.1))));
+                Some(paths)
+            }
+            Err(e) => {
+                results.push((
+                    "program paths",
+                    fail(
+                        format!("{} (working directory: {})", e, root_dir.display()),
+                        format!(
+                            "run anchor-lldb from the Anchor workspace root (the directory with Anchor.toml and programs/{}), or pass --idl and --program-crate-path",
+                            program.package
+                        ),
+                    ),
+                ));
+                None
+            }
+        },
+    };
+
+    match &paths {
+        Some((idl_path, crate_path)) => {
+            results.push(("idl", check_idl(idl_path, &program.package)));
+            results.push(("anchor-lang version", check_anchor_version(Path::new(crate_path), &root_dir)));
+            results.push(("idl freshness", check_idl_freshness(Path::new(idl_path), Path::new(crate_path))));
+        }
+        None => {
+            for name in ["idl", "anchor-lang version", "idl freshness"] {
+                results.push((name, Status::Skipped("the program paths are unknown".to_string())));
+            }
+        }
+    }
+
+    let mut all_passed = true;
+    for (name, status) in &results {
+        match status {
+            Status::Ok(detail) => println!("[ok]   {name}: {detail}"),
+            Status::Skipped(reason) => println!("[skip] {name}: {reason}"),
+            Status::Fail { problem, fix } => {
+                all_passed = false;
+                println!("[FAIL] {name}: {problem}\n       fix: {fix}");
+            }
+        }
+    }
+
+    Ok(all_passed)
+}
+
+fn check_cargo() -> Status {
+    match find_on_path("cargo") {
+        Some(path) => Status::Ok(format!("found at {}", path.display())),
+        None => fail(
+            "`cargo` is not on PATH, the debug wrapper cannot be built",
+            "install Rust with rustup (https://rustup.rs) and make sure ~/.cargo/bin is on PATH",
+        ),
+    }
+}
+
+fn check_lldb() -> Status {
+    match find_lldb() {
+        Some(lldb) => Status::Ok(format!("`{lldb}` will be used by `anchor-lldb debug`")),
+        None => fail(
+            "`lldb` is not on PATH, `anchor-lldb debug` cannot start the debugger",
+            "install lldb (`xcode-select --install` on macOS, `apt install lldb` on Debian/Ubuntu)",
+        ),
+    }
+}
+
+fn check_workspace(root_dir: &Path) -> Status {
+    let root_cargo = root_dir.join("Cargo.toml");
+
+    let Ok(content) = fs::read_to_string(&root_cargo) else {
+        return fail(
+            format!("no Cargo.toml in the working directory {}", root_dir.display()),
+            "run anchor-lldb from the root of the Anchor workspace",
+        );
+    };
+
+    match content.parse::<DocumentMut>() {
+        Ok(doc) if doc.get("workspace").is_some_and(Item::is_table_like) => {
+            Status::Ok(format!("{} has a [workspace] section", root_cargo.display()))
+        }
+        Ok(_) => fail(
+            format!("{} has no [workspace] section, the generated wrapper cannot be added to it", root_cargo.display()),
+            "add `[workspace]` with `members = [\"programs/*\"]` to the root Cargo.toml",
+        ),
+        Err(e) => fail(
+            format!("{} is not valid TOML: {}", root_cargo.display(), e),
+            "fix the syntax of the root Cargo.toml",
+        ),
+    }
+}
+
+fn check_idl(idl_path: &str, package: &str) -> Status {
+    let Ok(idl_json) = fs::read_to_string(idl_path) else {
+        return fail(
+            format!("no IDL at {}", idl_path),
+            format!("run `anchor build` (or `anchor idl build -p {package}`) to generate it under target/idl"),
+        );
+    };
+
+    match serde_json::from_str::<Idl>(&idl_json) {
+        Ok(idl) => Status::Ok(format!("{} ({} instructions)", idl_path, idl.instructions.len())),
+        Err(e) => fail(
+            format!("the IDL at {} cannot be parsed: {}", idl_path, e),
+            "rebuild it with Anchor 0.30 or newer, older IDL formats are not supported",
+        ),
+    }
+}
+
+// The version requirement of `anchor-lang` in `[dependencies]`, following `workspace = true` to the root manifest
+fn anchor_lang_requirement(crate_path: &Path, root_dir: &Path) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let doc = fs::read_to_string(crate_path.join("Cargo.toml"))?.parse::<DocumentMut>()?;
+    let Some(dep) = doc.get("dependencies").and_then(|deps| deps.get("anchor-lang")) else {
+        return Ok(None);
+    };
+
+    if dep.get("workspace").and_then(Item::as_bool) == Some(true) {
+        let root = fs::read_to_string(root_dir.join("Cargo.toml"))?.parse::<DocumentMut>()?;
+        let root_dep = root
+            .get("workspace")
+            .and_then(|w| w.get("dependencies"))
+            .and_then(|deps| deps.get("anchor-lang"))
+            .cloned();
+        return Ok(root_dep.as_ref().and_then(dependency_version));
+    }
+
+    Ok(dependency_version(dep))
+}
+
+fn dependency_version(dep: &Item) -> Option<String> {
+    dep.as_str()
+        .or_else(|| dep.get("version").and_then(Item::as_str))
+        .map(str::to_string)
+}
+
+// Cargo unifies the program's requirement with the wrapper's `expected` one (a `^` requirement) when a version
+// satisfies both. The lowest such version is `expected` itself or the lower bound of one of the program's comparators
+fn version_compatible(requirement: &str, expected: &str) -> bool {
+    let (Ok(required), Ok(wrapper)) = (VersionReq::parse(requirement.trim()), VersionReq::parse(expected)) else {
+        return false;
+    };
+
+    Version::parse(expected)
+        .into_iter()
+        .chain(required.comparators.iter().filter_map(lower_bound))
+        .any(|version| required.matches(&version) && wrapper.matches(&version))
+}
+
+fn lower_bound(comparator: &Comparator) -> Option<Version> {
+    let Comparator { major, minor, patch, .. } = *comparator;
+    match comparator.op {
+        Op::Less | Op::LessEq => None,
+        Op::Greater => Some(match (minor, patch) {
+            (Some(minor), Some(patch)) => Version::new(major, minor, patch + 1),
+            (Some(minor), None) => Version::new(major, minor + 1, 0),
+            _ => Version::new(major + 1, 0, 0),
+        }),
+        _ => Some(Version::new(major, minor.unwrap_or(0), patch.unwrap_or(0))),
+    }
+}
+
+fn check_anchor_version(crate_path: &Path, root_dir: &Path) -> Status {
+    match anchor_lang_requirement(crate_path, root_dir) {
+        Ok(Some(requirement)) if version_compatible(&requirement, ANCHOR_LANG_VERSION) => Status::Ok(format!(
+            "program uses anchor-lang \"{requirement}\", the wrapper uses \"{ANCHOR_LANG_VERSION}\""
+        )),
+        Ok(Some(requirement)) => fail(
+            format!("program uses anchor-lang \"{requirement}\" but the wrapper is built against \"{ANCHOR_LANG_VERSION}\""),
+            format!("use anchor-lang \"{ANCHOR_LANG_VERSION}\" in {}", crate_path.join("Cargo.toml").display()),
+        ),
+        Ok(None) => fail(
+            format!("no anchor-lang dependency in {}", crate_path.join("Cargo.toml").display()),
+            format!("add `anchor-lang = \"{ANCHOR_LANG_VERSION}\"` to the program's [dependencies]"),
+        ),
+        Err(e) => fail(
+            format!("cannot read {}: {}", crate_path.join("Cargo.toml").display(), e),
+            "check that --program-crate-path points at the program crate",
+        ),
+    }
+}
+
+fn newest_modification(dir: &Path) -> Option<SystemTime> {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+        .max()
+}
+
+fn check_idl_freshness(idl_path: &Path, crate_path: &Path) -> Status {
+    let Ok(idl_modified) = fs::metadata(idl_path).and_then(|m| m.modified()) else {
+        return Status::Skipped("the IDL does not exist".to_string());
+    };
+
+    match newest_modification(&crate_path.join("src")) {
+        Some(src_modified) if src_modified > idl_modified => fail(
+            "the program sources changed after the IDL was built, the wrapper would be generated from a stale IDL",
+            "run `anchor build` again",
+        ),
+        _ => Status::Ok("the IDL is newer than the program sources".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caret_requirements_match_up_to_the_first_non_zero_number() {
+        assert!(version_compatible("0.31.1", "0.31.1"));
+        assert!(version_compatible("0.31.0", "0.31.1"));
+        assert!(version_compatible("^0.31", "0.31.1"));
+        assert!(version_compatible(" 0.31.2 ", "0.31.1"));
+        assert!(version_compatible("0", "0.31.1"));
+        assert!(version_compatible("1.2", "1.5.0"));
+        assert!(!version_compatible("0.30.1", "0.31.1"));
+        assert!(!version_compatible("1.0", "0.31.1"));
+        assert!(!version_compatible("0.0.3", "0.0.4"));
+    }
+
+    #[test]
+    fn tilde_and_exact_requirements() {
+        assert!(version_compatible("~0.31.0", "0.31.1"));
+        assert!(!version_compatible("~1.2", "1.5.0"));
+        assert!(version_compatible("=0.31.1", "0.31.1"));
+        assert!(version_compatible("=0.31", "0.31.1"));
+        assert!(!version_compatible("=0.31.0", "0.31.1"));
+    }
+
+    #[test]
+    fn operator_and_comma_separated_requirements() {
+        assert!(version_compatible(">=0.30", "0.31.1"));
+        assert!(version_compatible(">=0.31, <0.32", "0.31.1"));
+        assert!(version_compatible(">0.31.0", "0.31.1"));
+        assert!(version_compatible(">=0.31.3, <0.32", "0.31.1"));
+        assert!(version_compatible("<=0.31.1", "0.31.1"));
+        assert!(version_compatible("0.31.*", "0.31.1"));
+        assert!(!version_compatible(">=0.32", "0.31.1"));
+        assert!(!version_compatible(">=0.30, <0.31", "0.31.1"));
+        assert!(!version_compatible("<0.31", "0.31.1"));
+        assert!(!version_compatible(">0.31", "0.31.1"));
+    }
+
+    #[test]
+    fn requirements_without_a_version_are_not_compatible() {
+        assert!(!version_compatible("", "0.31.1"));
+        assert!(!version_compatible("latest", "0.31.1"));
+    }
+}
@@ -1,12 +1,12 @@
 use anchor_idl::{Idl, IdlInstruction};
 
-use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use crate::generator::accounts::InstructionAcountCode;
-use crate::generator::args::InstructionArgCode;
+use crate::generator::args::{ArgCase, InstructionArgCode};
 use crate::generator::bumps::InstructionBumpsCode;
 use crate::generator::codegen::CodeGenerator;
+use crate::generator::config::{GenerationOptions, GeneratorConfig};
 use crate::utils::to_camel_case;
 
 /*
@@ -28,12 +28,18 @@ pub fn generate_wrapper(
     crate_path: &str,
     out_path: &PathBuf,
     package: &str,
+    standalone: bool,
+    options: GenerationOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let out_dir = Path::new(out_path).to_owned();
-    let code_generator = CodeGenerator::new(&idl, &crate_path, out_dir, &package);
+    let code_generator = CodeGenerator::new(idl, crate_path, out_dir, package, standalone, options)?;
 
     code_generator.generate_cargo_toml()?;
     code_generator.generate_mock_rs()?;
+    code_generator.generate_runner_rs()?;
+    code_generator.generate_repl_rs()?;
+    code_generator.generate_codec_rs()?;
+    code_generator.generate_random_rs()?;
     code_generator.generate_main_rs()?;
 
     Ok(())
@@ -41,43 +47,65 @@ pub fn generate_wrapper(
 
 pub fn generate_instruction_function(
     ix: &IdlInstruction,
-    account_map: &HashMap<String, String>,
-) -> String {
+    idl: &Idl,
+    config: &GeneratorConfig,
+    case: Option<&ArgCase>,
+) -> Result<String, String> {
     let ix_name = &ix.name;
-    let function_name = format!("call_{}", ix_name);
+    let function_name = match case {
+        Some(case) => format!("call_{}__{}", ix_name, case.name),
+        None => format!("call_{}", ix_name),
+    };
     let struct_name = to_camel_case(&ix.name);
     let bump_struct = format!("{}Bumps", struct_name);
 
-    let instruction_account = InstructionAcountCode::generate_account_code(ix, account_map);
-    let instuction_args = InstructionArgCode::generate_argument_code(ix);
-    let instruction_bumps = InstructionBumpsCode::generate_bumps_code(ix);
+    let arg_values = config.arg_values.get(ix_name);
+    let instruction_account = InstructionAcountCode::generate_account_code(ix, &idl.types, &idl.constants, &config.account_map, &config.mocks, config.entrypoint)?;
+    let instuction_args = InstructionArgCode::generate_argument_code(ix, &idl.types, arg_values, case)?;
+
+    if config.entrypoint {
+        return Ok(entrypoint_function(ix, &function_name, &struct_name, &instruction_account, &instuction_args));
+    }
+
+    let instruction_bumps = InstructionBumpsCode::generate_bumps_code(ix, &idl.types, &idl.constants, &config.account_map)?;
 
     // === Compose final Rust code ===
-    format!(
+    // The bindings are named like the IDL accounts and arguments, so the function's own locals start with `__`
+    Ok(format!(
         r#"
-fn {function_name}() {{
+fn {function_name}(__args: Option<&[u8]>) -> std::result::Result<(), String> {{
+    {args}
+
     {bindings}
 
-    let mut accounts = {struct_name} {{
+    let mut __accounts = {struct_name} {{
         {fields}
     }};
 
-    let account_infos = vec![{accounts_info_clones}];
-    let bumps = {bump_struct} {{ 
+    let __account_infos = vec![{accounts_info_clones}];
+    let __bumps = {bump_struct} {{ 
         {bump_fields}
     }};
 
-    let ctx = Context::new(
+    let __ctx = Context::new(
         &PROGRAM_ID,
-        &mut accounts,
-        &account_infos,
-        bumps
+        &mut __accounts,
+        &__account_infos,
+        __bumps
     );
-    {args}
 
-    match {ix_name}({call_args}) {{
-        Ok(_) => println!("{ix_name} succeeded"),
-        Err(e) => eprintln!("{ix_name} failed: {{:?}}", e),
+    // Like Anchor's dispatcher, serialize the accounts back so their state is visible afterwards
+    let __result = {ix_name}({call_args}).and_then(|_| __accounts.exit(&PROGRAM_ID));
+
+    match __result {{
+        Ok(_) => {{
+            println!("{ix_name} succeeded");
+            Ok(())
+        }}
+        Err(e) => {{
+            eprintln!("{ix_name} failed: {{:?}}", e);
+            Err(format!("{{:?}}", e))
+        }}
     }}
 }}
     "#,
@@ -89,6 +117,54 @@ fn {function_name}() {{
         args = instuction_args.args.join("\n    "),
         call_args = instuction_args.call_args.join(", "),
         bump_fields = instruction_bumps.bump_fields.join(",\n     "),
-    )
+    ))
 }
 
+
+// `--entrypoint`: the instruction data (IDL discriminator and Borsh encoded arguments) goes through the program's
+// `entry`, so Anchor's dispatcher runs `try_accounts` (every constraint), the handler and `exit()` like on chain.
+// The arguments are bound first, the PDA mocks take the bytes of their argument seeds from them
+fn entrypoint_function(
+    ix: &IdlInstruction,
+    function_name: &str,
+    struct_name: &str,
+    instruction_account: &InstructionAcountCode,
+    instruction_args: &InstructionArgCode,
+) -> String {
+    let ix_name = &ix.name;
+    let discriminator: Vec<String> = ix.discriminator.iter().map(|b| format!("{}u8", b)).collect();
+    let fields: Vec<&str> = ix.args.iter().map(|arg| arg.name.as_str()).collect();
+
+    format!(
+        r#"
+fn {function_name}(__args: Option<&[u8]>) -> std::result::Result<(), String> {{
+    {args}
+
+    {bindings}
+
+    let mut __ix_data: Vec<u8> = vec![{discriminator}];
+    AnchorSerialize::serialize(&cr::instruction::{struct_name} {{ {fields} }}, &mut __ix_data)
+        .map_err(|e| format!("failed to encode the arguments: {{}}", e))?;
+
+    // `entry` ties the slice to the lifetime of the accounts, which is `'static` like the fixtures
+    let __account_infos: &'static [AccountInfo<'static>] = Box::leak(vec![{accounts_info_clones}].into_boxed_slice());
+
+    match cr::entry(&PROGRAM_ID, __account_infos, &__ix_data) {{
+        Ok(_) => {{
+            println!("{ix_name} succeeded");
+            Ok(())
+        }}
+        Err(e) => {{
+            eprintln!("{ix_name} failed: {{:?}}", e);
+            Err(format!("{{:?}}", e))
+        }}
+    }}
+}}
+    "#,
+        discriminator = discriminator.join(", "),
+        args = instruction_args.args.join("\n    "),
+        fields = fields.join(", "),
+        bindings = instruction_account.bindings.join("\n    "),
+        accounts_info_clones = instruction_account.account_infos.join(", "),
+    )
+}
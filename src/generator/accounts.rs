@@ -1,6 +1,140 @@
-use crate::utils::visit_account_item;
-use anchor_idl::{IdlInstruction, IdlInstructionAccount};
+use crate::generator::bumps::InstructionBumpsCode;
+use crate::generator::generics::zeroed_size;
+use crate::generator::known_accounts::{
+    BPF_LOADER_UPGRADEABLE_ID, INSTRUCTIONS_SYSVAR_ID, KnownAccount, KnownKind, SYSTEM_PROGRAM_ID, TOKEN_2022_PROGRAM_ID,
+    TOKEN_PROGRAM_ID, known_account,
+};
+use crate::generator::token::{TokenKind, associated_token, instruction_token_program, token_kind};
+use crate::generator::config::MockSettings;
+use crate::generator::token_config::TokenMockConfig;
+use crate::utils::{account_paths, binding_name, scoped_path, to_camel_case};
+use anchor_idl::{IdlConst, IdlInstruction, IdlInstructionAccount, IdlInstructionAccountItem, IdlPda, IdlSeed, IdlType, IdlTypeDef};
 use std::collections::HashMap;
+use std::str::FromStr;
+
+// Token mocks: decimals of a mint, supply of a mint and balance of a token account
+const MOCK_MINT_DECIMALS: u8 = 6;
+const MOCK_TOKEN_AMOUNT: u64 = 1_000_000_000;
+
+/// How an instruction account gets mocked
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AccountMockKind {
+    Program,
+    Sysvar,
+    Mint,
+    TokenAccount,
+    TokenInterface, // `Interface<'info, TokenInterface>`: Token or Token-2022
+    Signer,
+    Pda,
+}
+
+impl AccountMockKind {
+    pub fn classify(
+        account: &IdlInstructionAccount,
+        account_map: &HashMap<String, String>,
+        types: &[IdlTypeDef],
+    ) -> AccountMockKind {
+        let known = account.address.as_deref().map(known_account);
+        if let Some(Some(KnownAccount { kind: KnownKind::Sysvar { .. }, .. })) = known {
+            return AccountMockKind::Sysvar;
+        }
+
+        // Token accounts can have a fixed address too, e.g. `#[account(address = USDC_MINT)]`
+        match token_kind(account, account_map, types) {
+            Some(TokenKind::Mint) => return AccountMockKind::Mint,
+            Some(TokenKind::TokenAccount) => return AccountMockKind::TokenAccount,
+            Some(TokenKind::Program) => return AccountMockKind::TokenInterface,
+            None => {}
+        }
+
+        match known {
+            Some(Some(KnownAccount { kind: KnownKind::Program { .. }, .. })) => AccountMockKind::Program,
+            // Any other fixed address is a program only when the field is one (or has no type, e.g. an `UncheckedAccount`
+            // CPI target), an `Account<'info, T>` is mocked as a data account at that address
+            Some(None) if account_map.get(&account.name).is_none_or(|ty| is_program_type(ty)) => AccountMockKind::Program,
+            _ if is_legacy_system_program(account) => AccountMockKind::Program,
+            _ if account.signer => AccountMockKind::Signer,
+            _ => AccountMockKind::Pda,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AccountMockKind::Program | AccountMockKind::TokenInterface => "program",
+            AccountMockKind::Sysvar => "sysvar",
+            AccountMockKind::Mint => "mint",
+            AccountMockKind::TokenAccount => "token",
+            AccountMockKind::Signer => "signer",
+            AccountMockKind::Pda => "pda",
+        }
+    }
+}
+
+// Older IDLs have no `address`, the system program is only known by its name
+fn is_legacy_system_program(account: &IdlInstructionAccount) -> bool {
+    account.address.is_none() && account.name.to_lowercase() == "system_program"
+}
+
+// `Program<'info, T>` and `Interface<'info, T>` fields, see `extract_account_struct_map`
+fn is_program_type(type_name: &str) -> bool {
+    type_name.starts_with("Program<") || type_name.starts_with("Interface<")
+}
+
+/// Fixed key of an account: its IDL `address`, the System Program for a `system_program` of an older IDL
+pub fn fixed_address(account: &IdlInstructionAccount) -> Option<&str> {
+    match &account.address {
+        Some(address) => Some(address),
+        None if is_legacy_system_program(account) => Some(SYSTEM_PROGRAM_ID),
+        None => None,
+    }
+}
+
+/// Type of the nested `Accounts` struct of a composite group, from the program sources, `CamelCase` of the field otherwise
+pub fn composite_struct_name(group_name: &str, account_map: &HashMap<String, String>) -> String {
+    account_map
+        .get(group_name)
+        .cloned()
+        .unwrap_or_else(|| to_camel_case(group_name))
+}
+
+// Everything the mocks of one instruction are generated from. Token mocks default to what the IDL does not tell
+// (`token::mint`, `mint::authority`, ... constraints are not part of it): the first signer is the authority and owner,
+// the first mint the mint of every token account
+struct MockContext<'a> {
+    ix: &'a IdlInstruction,
+    types: &'a [IdlTypeDef],
+    constants: &'a [IdlConst],
+    account_map: &'a HashMap<String, String>,
+    mocks: &'a MockSettings,
+    entrypoint: bool,      // `--entrypoint`: Anchor checks the `seeds` of the PDAs
+    accounts: Vec<String>, // names of the bindings the mocks can take keys from
+    authority: Option<String>, // binding of the first signer
+    mint: Option<String>,      // binding of the first mint
+    token_program: &'static str,
+}
+
+impl MockContext<'_> {
+    // Key of another account of the instruction, bound before the one using it
+    fn key_of(&self, name: Option<&String>, fallback: &str) -> String {
+        match name {
+            Some(name) if self.accounts.contains(name) => format!("*{}.key", name),
+            _ => format!(r#"mock_pubkey("{}")"#, fallback),
+        }
+    }
+
+    // Owner of a token mock: its `--token-config` program, the instruction's token program otherwise
+    fn token_program(&self, config: &TokenMockConfig) -> &'static str {
+        config.program.map_or(self.token_program, |program| program.id())
+    }
+}
+
+// A mock binding and the bindings its key is made from
+struct PendingBinding {
+    rank: u8, // order of the bindings without dependencies between them: others, mints, token accounts, instructions sysvar
+    name: String,
+    dependencies: Vec<String>,
+    code: String,
+}
 
 pub struct InstructionAcountCode {
     pub bindings: Vec<String>,
@@ -9,59 +143,430 @@ pub struct InstructionAcountCode {
 }
 
 impl InstructionAcountCode {
+    /// Fails when the `--token-config` of an account does not fit it, e.g. an extension of another account kind, and
+    /// with `entrypoint` when the address of a PDA cannot be derived from its seeds
     pub fn generate_account_code(
         ix: &IdlInstruction,
+        types: &[IdlTypeDef],
+        constants: &[IdlConst],
         account_map: &HashMap<String, String>,
-    ) -> InstructionAcountCode {
+        mocks: &MockSettings,
+        entrypoint: bool,
+    ) -> Result<InstructionAcountCode, String> {
+        let tokens = &mocks.tokens;
+        let paths = account_paths(&ix.accounts);
+        let accounts: Vec<&IdlInstructionAccount> = paths.iter().map(|(_, account)| *account).collect();
+        let first_binding = |wanted: AccountMockKind| {
+            paths
+                .iter()
+                .find(|(_, account)| AccountMockKind::classify(account, account_map, types) == wanted)
+                .map(|(path, _)| binding_name(path))
+        };
+        let context = MockContext {
+            ix,
+            types,
+            constants,
+            account_map,
+            mocks,
+            entrypoint,
+            accounts: paths.iter().map(|(path, _)| binding_name(path)).collect(),
+            authority: first_binding(AccountMockKind::Signer),
+            mint: first_binding(AccountMockKind::Mint),
+            token_program: tokens.program.map_or_else(|| instruction_token_program(&accounts), |program| program.id()),
+        };
+
+        let mut code = InstructionAcountCode {
+            bindings: vec![],
+            fields: vec![],
+            account_infos: vec![],
+        };
+        let mut pending = vec![];
+        code.fields = code
+            .group_fields(&ix.accounts, &context, &mut pending, "")
+            .map_err(|e| format!("instruction `{}`, {}", ix.name, e))?;
+        code.bindings = Self::order_bindings(pending).map_err(|e| format!("instruction `{}`, {}", ix.name, e))?;
+
+        Ok(code)
+    }
+
+    // Every binding comes after the ones its key is made from: mints take the key of their authority, token accounts
+    // the ones of their mint and owner, PDAs of `--entrypoint` the ones of their seeds and the instructions sysvar the keys
+    // of every account. Otherwise they keep the order of their rank, then of their declaration
+    fn order_bindings(mut pending: Vec<PendingBinding>) -> Result<Vec<String>, String> {
+        pending.sort_by_key(|binding| binding.rank);
+
+        let mut bound: Vec<String> = vec![];
         let mut bindings = vec![];
+        while !pending.is_empty() {
+            let ready = pending.iter().position(|binding| {
+                binding
+                    .dependencies
+                    .iter()
+                    .all(|dependency| dependency == &binding.name || bound.contains(dependency))
+            });
+            let Some(ready) = ready else {
+                let names: Vec<&str> = pending.iter().map(|binding| binding.name.as_str()).collect();
+                return Err(format!("the keys of `{}` depend on each other", names.join("`, `")));
+            };
+
+            let binding = pending.remove(ready);
+            bound.push(binding.name);
+            bindings.push(binding.code);
+        }
+
+        Ok(bindings)
+    }
+
+    // Binds a mock for every account of `items`, nested groups included, and returns the fields of their struct literal.
+    // The `AccountInfo`s are listed in declaration order, the order `try_accounts` consumes them in.
+    // `scope` is the path of the group, its accounts are bound and kept as fixtures by their path (`from_vault`,
+    // `"from.vault"`) so groups with the same account names get their own mocks
+    fn group_fields(
+        &mut self,
+        items: &[IdlInstructionAccountItem],
+        context: &MockContext,
+        pending: &mut Vec<PendingBinding>,
+        scope: &str,
+    ) -> Result<Vec<String>, String> {
         let mut fields = vec![];
-        let mut accounts_info_clones = vec![];
 
-        for acc in &ix.accounts {
-            let account: &IdlInstructionAccount =
-                visit_account_item(acc).expect("Failed te retrieve accounts");
+        for item in items {
+            let account = match item {
+                IdlInstructionAccountItem::Single(account) => account,
+                IdlInstructionAccountItem::Composite(group) => {
+                    let struct_name = composite_struct_name(&group.name, context.account_map);
+                    let nested = self.group_fields(&group.accounts, context, pending, &scoped_path(scope, &group.name))?;
+                    fields.push(format!("{}: {} {{ {} }}", group.name, struct_name, nested.join(", ")));
+                    continue;
+                }
+            };
             let acc_name: &String = &account.name;
+            let path = scoped_path(scope, acc_name);
+            let binding_name = binding_name(&path);
+            let kind = AccountMockKind::classify(account, context.account_map, context.types);
+            let mut dependencies = vec![];
+            let mock_call = Self::mock_call(account, &path, kind, context, &mut dependencies)
+                .map_err(|e| format!("account `{}`: {}", path, e))?;
+
+            // Fixtures are reused between calls of a REPL session, a fresh mock is created otherwise
+            let rank = match kind {
+                AccountMockKind::Mint => 1,
+                AccountMockKind::TokenAccount => 2,
+                AccountMockKind::Sysvar if fixed_address(account) == Some(INSTRUCTIONS_SYSVAR_ID) => 3,
+                _ => 0,
+            };
+            pending.push(PendingBinding {
+                rank,
+                code: format!(r#"let {binding_name} = fixture("{path}", || {mock_call});"#),
+                name: binding_name.clone(),
+                dependencies,
+            });
+
+            // The field type of the struct picks the wrapper (`Account`, `Program`, `Sysvar`, `InterfaceAccount`, ...)
+            fields.push(format!(r#"{acc_name}: account_field("{path}", {binding_name})"#));
+
+            self.account_infos.push(format!("{binding_name}.clone()"));
+        }
+
+        Ok(fields)
+    }
 
-            let mock_call: String = if acc_name.to_lowercase() == "system_program" {
-                format!(r#"Box::leak(Box::new(mock_system_program()))"#)
-            } else if account.signer {
-                format!(r#"Box::leak(Box::new(mock_signer_account("{acc_name}")))"#)
-            } else {
-                let struct_name = account_map.get(acc_name)
-                    .expect("Account struct name not found, maybe you don't have it in lib.rs and anchor-lldb cannot use it to derive account discriminator.");
+    // `path` is the path of the account, mocks without a fixed address or constant seeds take their key from it.
+    // The bindings the mock takes keys from are added to `dependencies`
+    fn mock_call(
+        account: &IdlInstructionAccount,
+        path: &str,
+        kind: AccountMockKind,
+        context: &MockContext,
+        dependencies: &mut Vec<String>,
+    ) -> Result<String, String> {
+        let acc_name = &account.name;
+        let scope = path.rsplit_once('.').map_or("", |(scope, _)| scope);
+        let token_config = context.mocks.tokens.accounts.get(acc_name).cloned().unwrap_or_default();
+
+        let mock_call = match kind {
+            AccountMockKind::Program => Self::program_mock(fixed_address(account).unwrap_or(SYSTEM_PROGRAM_ID)),
+            AccountMockKind::TokenInterface => Self::program_mock(context.token_program(&token_config)),
+            AccountMockKind::Sysvar => {
+                let address = fixed_address(account).unwrap_or_default();
+                match known_account(address).map(|known| known.kind) {
+                    _ if address == INSTRUCTIONS_SYSVAR_ID => {
+                        dependencies.extend(context.accounts.iter().cloned());
+                        Self::instructions_sysvar_mock(context)
+                    }
+                    Some(KnownKind::Sysvar { layout: Some("Clock") }) => {
+                        format!(r#"mock_sysvar_account(pubkey!("{address}"), {})"#, context.mocks.clock.expression())
+                    }
+                    Some(KnownKind::Sysvar { layout: Some(layout) }) => {
+                        format!(r#"mock_sysvar_account(pubkey!("{address}"), {layout}::default())"#)
+                    }
+                    _ => format!(r#"mock_raw_sysvar_account(pubkey!("{address}"))"#),
+                }
+            }
+            AccountMockKind::Mint => {
+                let key = Self::mock_address(account, path, context, dependencies)?;
+                let token_program = context.token_program(&token_config);
+                dependencies.extend(context.authority.iter().cloned());
+                let authority = match &context.authority {
+                    Some(authority) => format!("Some(*{}.key)", authority),
+                    None => "None".to_string(),
+                };
+                let extensions = Self::token_extensions(&token_config, TokenKind::Mint, &key, token_program)?;
                 format!(
-                    r#"Box::leak(Box::new(mock_pda_account::<{}>(&[b"{acc_name}"], &PROGRAM_ID, 64)))"#,
-                    struct_name
+                    r#"mock_mint({}, pubkey!("{}"), {}, {}, {}, &[{}])"#,
+                    key,
+                    token_program,
+                    authority,
+                    token_config.decimals.unwrap_or(MOCK_MINT_DECIMALS),
+                    token_config.supply.unwrap_or(MOCK_TOKEN_AMOUNT),
+                    extensions.join(", ")
                 )
-            };
+            }
+            AccountMockKind::TokenAccount => {
+                let (key, mint, owner, token_program) = match associated_token(account) {
+                    Some(ata) => {
+                        let wallet_binding = binding_name(&scoped_path(scope, &ata.wallet));
+                        let mint_binding = binding_name(&scoped_path(scope, &ata.mint));
+                        let wallet = context.key_of(Some(&wallet_binding), &ata.wallet);
+                        let mint = context.key_of(Some(&mint_binding), &ata.mint);
+                        dependencies.extend([wallet_binding, mint_binding]);
+                        let token_program = match &ata.token_program {
+                            Some(token_program) => known_account(token_program).map_or(TOKEN_PROGRAM_ID, |known| known.address),
+                            None => context.token_program(&token_config),
+                        };
+                        let key = format!(r#"associated_token_address(&{wallet}, &{mint}, &pubkey!("{token_program}"))"#);
+                        (key, mint, wallet, token_program)
+                    }
+                    None => {
+                        dependencies.extend(context.mint.iter().chain(&context.authority).cloned());
+                        (
+                            Self::mock_address(account, path, context, dependencies)?,
+                            context.key_of(context.mint.as_ref(), "mint"),
+                            context.key_of(context.authority.as_ref(), "owner"),
+                            context.token_program(&token_config),
+                        )
+                    }
+                };
+                let delegate = match &token_config.delegate {
+                    Some(delegate) => format!(r#"Some(pubkey!("{delegate}"))"#),
+                    None => "None".to_string(),
+                };
+                let extensions = Self::token_extensions(&token_config, TokenKind::TokenAccount, &mint, token_program)?;
+                format!(
+                    r#"mock_token_account({key}, pubkey!("{token_program}"), {mint}, {owner}, {}, {delegate}, &[{}])"#,
+                    token_config.amount.unwrap_or(MOCK_TOKEN_AMOUNT),
+                    extensions.join(", ")
+                )
+            }
+            AccountMockKind::Signer => format!(r#"mock_signer_account("{path}")"#),
+            AccountMockKind::Pda => {
+                // Its type gives the account discriminator, `check` reports the same error
+                let struct_name = context
+                    .account_map
+                    .get(acc_name)
+                    .ok_or("no `Account<'info, T>` field with this name found in the program sources")?;
+                let size = Self::account_size(struct_name, context.types);
+                match (fixed_address(account), &account.pda) {
+                    (Some(address), _) => format!(r#"mock_data_account::<{struct_name}>(pubkey!("{address}"), &PROGRAM_ID, {size})"#),
+                    (None, Some(pda)) if context.entrypoint => {
+                        let key = Self::derived_address(pda, scope, context, dependencies)?;
+                        format!(r#"mock_data_account::<{struct_name}>({key}, &PROGRAM_ID, {size})"#)
+                    }
+                    (None, _) => format!(
+                        r#"mock_pda_account::<{}>(&[{}], &PROGRAM_ID, {})"#,
+                        struct_name,
+                        Self::pda_seeds(account, path, context.constants),
+                        size
+                    ),
+                }
+            }
+        };
+
+        Ok(mock_call)
+    }
 
-            bindings.push(format!("let {acc_name} = {mock_call};"));
+    // The instructions sysvar of a transaction made of the called instruction only: its accounts and its discriminator,
+    // the arguments are only known when it is called
+    fn instructions_sysvar_mock(context: &MockContext) -> String {
+        let metas: Vec<String> = account_paths(&context.ix.accounts)
+            .iter()
+            .map(|(path, account)| {
+                let key = match fixed_address(account) {
+                    Some(INSTRUCTIONS_SYSVAR_ID) => format!(r#"pubkey!("{}")"#, INSTRUCTIONS_SYSVAR_ID),
+                    _ => format!("*{}.key", binding_name(path)),
+                };
+                let constructor = if account.writable { "new" } else { "new_readonly" };
+                format!("AccountMeta::{}({}, {})", constructor, key, account.signer)
+            })
+            .collect();
+        let data: Vec<String> = context.ix.discriminator.iter().map(|b| format!("{}u8", b)).collect();
 
-            let account_type = if account.signer {
-                "Signer"
-            } else if acc_name.to_lowercase() == "system_program" {
-                "Program"
-            } else {
-                "Account"
-            };
+        format!(
+            r#"mock_instructions_sysvar(pubkey!("{}"), &[anchor_lang::solana_program::instruction::Instruction {{ program_id: PROGRAM_ID, accounts: vec![{}], data: vec![{}] }}], 0)"#,
+            INSTRUCTIONS_SYSVAR_ID,
+            metas.join(", "),
+            data.join(", ")
+        )
+    }
 
-            let prefix = if acc_name.to_lowercase() == "system_program" {
-                "&*"
-            } else {
-                ""
-            };
+    // Executable account owned by the loader of a well-known program, the upgradeable loader for any other one
+    fn program_mock(address: &str) -> String {
+        let loader = match known_account(address).map(|known| known.kind) {
+            Some(KnownKind::Program { loader }) => loader,
+            _ => BPF_LOADER_UPGRADEABLE_ID,
+        };
+        format!(r#"mock_program_account(pubkey!("{address}"), pubkey!("{loader}"))"#)
+    }
 
-            fields.push(format!(
-                r#"{acc_name}: {account_type}::try_from({prefix}{acc_name}).unwrap()"#
-            ));
+    // Token-2022 extensions of a token mock, the Token program has none
+    fn token_extensions(
+        config: &TokenMockConfig,
+        kind: TokenKind,
+        mint_key: &str,
+        token_program: &str,
+    ) -> Result<Vec<String>, String> {
+        let extensions = config.extensions.expressions(kind, mint_key)?;
+        if !extensions.is_empty() && token_program != TOKEN_2022_PROGRAM_ID {
+            return Err("token extensions need the Token-2022 program (`\"program\": \"token-2022\"` or --token-program token-2022)".to_string());
+        }
+        Ok(extensions)
+    }
 
-            accounts_info_clones.push(format!("{acc_name}.clone()"));
+    // Constant seeds give the address Anchor's `seeds` constraint expects, the account path is used otherwise
+    fn pda_seeds(account: &IdlInstructionAccount, path: &str, constants: &[IdlConst]) -> String {
+        match &account.pda {
+            Some(pda) if pda.program.is_none() && pda.seeds.iter().all(|s| matches!(s, IdlSeed::Const(_))) => {
+                InstructionBumpsCode::seed_exprs(pda, constants, "", &[], &[]).unwrap_or_default().join(", ")
+            }
+            _ => format!(r#"b"{}""#, path),
         }
+    }
 
-        InstructionAcountCode {
-            bindings,
-            fields,
-            account_infos: accounts_info_clones,
+    // Address of a PDA from its actual seeds, which `--entrypoint` needs for Anchor's `seeds` constraint to hold.
+    // The accounts of its seeds are bound before it (added to `dependencies`), the arguments before every account
+    fn derived_address(
+        pda: &IdlPda,
+        scope: &str,
+        context: &MockContext,
+        dependencies: &mut Vec<String>,
+    ) -> Result<String, String> {
+        let mut account_key = |path: &str| {
+            // Further segments are fields of the account's data, which is not known when the mocks are created
+            if path.contains('.') {
+                return Err(format!("seed `{}` is a field of an account's data, the PDA address cannot be derived", path));
+            }
+            let binding = binding_name(&scoped_path(scope, path));
+            if !context.accounts.contains(&binding) {
+                return Err(format!("seed account `{}` is not an account of the instruction", path));
+            }
+            dependencies.push(binding.clone());
+            Ok(binding)
+        };
+
+        for seed in &pda.seeds {
+            if let IdlSeed::Account(seed_account) = seed {
+                account_key(&seed_account.path)?;
+            }
         }
+        let program = match &pda.program {
+            None => "&PROGRAM_ID".to_string(),
+            Some(IdlSeed::Const(program)) if program.value.len() == 32 => format!("&Pubkey::new_from_array({:?})", program.value),
+            Some(IdlSeed::Account(program)) => format!("{}.key", account_key(&program.path)?),
+            Some(_) => return Err("the program of the PDA is not a constant or an account, its address cannot be derived".to_string()),
+        };
+        let seeds = InstructionBumpsCode::seed_exprs(pda, context.constants, scope, &context.ix.args, context.types)?;
+
+        // An argument seed longer than 32 bytes has no address, Anchor's `seeds` constraint then fails like on chain
+        Ok(format!(
+            "Pubkey::try_find_program_address(&[{}], {}).map_or(Pubkey::default(), |(key, _)| key)",
+            seeds.join(", "),
+            program
+        ))
+    }
+
+    // Key of a token mock: its fixed address, the program's PDA of its seeds (only the constant ones without
+    // `--entrypoint`), a key derived from its name otherwise
+    fn mock_address(
+        account: &IdlInstructionAccount,
+        path: &str,
+        context: &MockContext,
+        dependencies: &mut Vec<String>,
+    ) -> Result<String, String> {
+        let scope = path.rsplit_once('.').map_or("", |(scope, _)| scope);
+        let address = match (&account.address, &account.pda) {
+            (Some(address), _) => format!(r#"pubkey!("{address}")"#),
+            (None, Some(pda)) if context.entrypoint => Self::derived_address(pda, scope, context, dependencies)?,
+            (None, Some(_)) => format!(
+                "Pubkey::find_program_address(&[{}], &PROGRAM_ID).0",
+                Self::pda_seeds(account, path, context.constants)
+            ),
+            (None, None) => format!(r#"mock_pubkey("{}")"#, path),
+        };
+        Ok(address)
+    }
+
+    // Discriminator plus the zeroed Borsh layout of the account type (generic arguments substituted), 64 when unknown
+    fn account_size(struct_name: &str, types: &[IdlTypeDef]) -> usize {
+        IdlType::from_str(struct_name)
+            .ok()
+            .and_then(|ty| zeroed_size(&ty, types))
+            .map_or(64, |size| 8 + size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn instruction(accounts: serde_json::Value) -> IdlInstruction {
+        serde_json::from_value(json!({
+            "name": "seeded",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": accounts,
+            "args": [{ "name": "id", "type": "u64" }]
+        }))
+        .unwrap()
+    }
+
+    fn bindings(ix: &IdlInstruction, entrypoint: bool) -> Result<Vec<String>, String> {
+        let account_map = HashMap::from([("escrow".to_string(), "Escrow".to_string())]);
+        InstructionAcountCode::generate_account_code(ix, &[], &[], &account_map, &MockSettings::default(), entrypoint)
+            .map(|code| code.bindings)
+    }
+
+    #[test]
+    fn entrypoint_pdas_are_bound_after_their_seed_accounts_at_their_derived_address() {
+        let ix = instruction(json!([
+            { "name": "escrow", "pda": { "seeds": [
+                { "kind": "const", "value": [101] },
+                { "kind": "account", "path": "authority" },
+                { "kind": "arg", "path": "id" }
+            ] } },
+            { "name": "authority", "signer": true }
+        ]));
+
+        let derived = bindings(&ix, true).unwrap();
+        assert!(derived[0].starts_with("let authority = "));
+        assert!(derived[1].contains(
+            "Pubkey::try_find_program_address(&[&[101], authority.key().as_ref(), &id.to_le_bytes()], &PROGRAM_ID)"
+        ));
+
+        // Without `--entrypoint` the seeds are not checked, the mock keeps its declaration order and path seed
+        let handler = bindings(&ix, false).unwrap();
+        assert!(handler[0].contains(r#"mock_pda_account::<Escrow>(&[b"escrow"], &PROGRAM_ID, 64)"#));
+    }
+
+    #[test]
+    fn entrypoint_pdas_without_a_derivable_address_are_errors() {
+        let ix = instruction(json!([
+            { "name": "escrow", "pda": { "seeds": [{ "kind": "account", "path": "authority.owner" }] } },
+            { "name": "authority", "signer": true }
+        ]));
+        assert_eq!(
+            bindings(&ix, true).unwrap_err(),
+            "instruction `seeded`, account `escrow`: seed `authority.owner` is a field of an account's data, the PDA address cannot be derived"
+        );
+        assert!(bindings(&ix, false).is_ok());
     }
 }
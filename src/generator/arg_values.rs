@@ -0,0 +1,373 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::str::FromStr;
+
+use anchor_idl::{Idl, IdlArrayLen, IdlDefinedFields, IdlGenericArg, IdlType, IdlTypeDef, IdlTypeDefTy};
+use base64::Engine;
+use serde_json::Value;
+
+use crate::generator::generics::instantiate;
+use crate::int256::le_bytes_256;
+use crate::utils::idl_type_to_string;
+
+/*
+Argument values given with `--args <file.json>`, turned into Rust literals of the argument's IDL type.
+
+    { "<instruction>": { "<argument>": <JSON value> } }
+
+Pubkeys are base58 strings, bytes are base64 strings (or hex with a `0x` prefix, or an array of numbers),
+structs are objects (arrays for tuple structs) and enums are `"Variant"` or `{ "Variant": <fields> }`.
+*/
+
+/// instruction name -> argument name -> JSON value
+pub type ArgValues = HashMap<String, HashMap<String, Value>>;
+
+/// Reads the `--args` file, every instruction and argument in it has to exist in the IDL
+pub fn load_arg_values(path: &str, idl: &Idl) -> Result<ArgValues, Box<dyn std::error::Error>> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Failed to read the argument values at {}: {}", path, e))?;
+    let values: ArgValues = serde_json::from_str(&json).map_err(|e| {
+        format!("Failed to parse the argument values at {}, expected {{ \"<instruction>\": {{ \"<argument>\": <value> }} }}: {}", path, e)
+    })?;
+
+    for (ix_name, args) in &values {
+        let ix = idl
+            .instructions
+            .iter()
+            .find(|ix| &ix.name == ix_name)
+            .ok_or(format!("{}: instruction `{}` not found in the IDL", path, ix_name))?;
+
+        if let Some(arg_name) = args.keys().find(|name| !ix.args.iter().any(|arg| &&arg.name == name)) {
+            return Err(format!("{}: instruction `{}` has no argument `{}`", path, ix_name, arg_name).into());
+        }
+    }
+
+    Ok(values)
+}
+
+/// Rust expression of `value` interpreted as the IDL type `ty`
+pub fn literal_value(value: &Value, ty: &IdlType, types: &[IdlTypeDef]) -> Result<String, String> {
+    let mismatch = || format!("expected {}, got {}", idl_type_to_string(ty), value);
+
+    let literal = match ty {
+        IdlType::Bool => value.as_bool().ok_or_else(mismatch)?.to_string(),
+        IdlType::U8 => integer_literal::<u8>(value, "u8")?,
+        IdlType::I8 => integer_literal::<i8>(value, "i8")?,
+        IdlType::U16 => integer_literal::<u16>(value, "u16")?,
+        IdlType::I16 => integer_literal::<i16>(value, "i16")?,
+        IdlType::U32 => integer_literal::<u32>(value, "u32")?,
+        IdlType::I32 => integer_literal::<i32>(value, "i32")?,
+        IdlType::U64 => integer_literal::<u64>(value, "u64")?,
+        IdlType::I64 => integer_literal::<i64>(value, "i64")?,
+        IdlType::U128 => integer_literal::<u128>(value, "u128")?,
+        IdlType::I128 => integer_literal::<i128>(value, "i128")?,
+        IdlType::F32 => format!("{}f32", value.as_f64().ok_or_else(mismatch)? as f32),
+        IdlType::F64 => format!("{}f64", value.as_f64().ok_or_else(mismatch)?),
+        // No native 256-bit integer: the program's own type is decoded from its little endian Borsh bytes
+        IdlType::U256 | IdlType::I256 => {
+            let bytes = number_text(value)
+                .and_then(|text| le_bytes_256(&text, matches!(ty, IdlType::I256)))
+                .ok_or_else(mismatch)?;
+            format!("AnchorDeserialize::deserialize(&mut &{}[..]).unwrap()", byte_array(&bytes))
+        }
+        IdlType::String => format!("{:?}.to_string()", value.as_str().ok_or_else(mismatch)?),
+        IdlType::Pubkey => {
+            let text = value.as_str().ok_or_else(mismatch)?;
+            let key: [u8; 32] = bs58::decode(text)
+                .into_vec()
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or(format!("expected a base58 pubkey, got {}", value))?;
+            format!("Pubkey::new_from_array({})", byte_array(&key))
+        }
+        IdlType::Bytes => format!("{}.to_vec()", byte_array(&bytes_value(value)?)),
+        IdlType::Option(inner) => match value {
+            Value::Null => "None".to_string(),
+            _ => format!("Some({})", literal_value(value, inner, types)?),
+        },
+        IdlType::Vec(inner) => {
+            let items = value.as_array().ok_or_else(mismatch)?;
+            format!("vec![{}]", items_literal(items, inner, types)?)
+        }
+        IdlType::Array(inner, len) => {
+            let items = value.as_array().ok_or_else(mismatch)?;
+            if let IdlArrayLen::Value(n) = len
+                && items.len() != *n
+            {
+                return Err(format!("expected {} items, got {}", n, items.len()));
+            }
+            format!("[{}]", items_literal(items, inner, types)?)
+        }
+        IdlType::Defined { name, generics } => defined_literal(value, name, generics, types)?,
+        IdlType::Generic(name) => return Err(format!("generic type `{}` is not supported", name)),
+        _ => return Err(format!("unsupported type {}", idl_type_to_string(ty))),
+    };
+
+    Ok(literal)
+}
+
+fn number_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+// Integers may also be strings, JSON numbers cannot hold every 128-bit value
+fn integer_literal<T: FromStr + Display>(value: &Value, suffix: &str) -> Result<String, String> {
+    let n: T = number_text(value)
+        .and_then(|text| text.parse().ok())
+        .ok_or(format!("expected {}, got {}", suffix, value))?;
+    Ok(format!("{}{}", n, suffix))
+}
+
+fn byte_array(bytes: &[u8]) -> String {
+    let bytes: Vec<String> = bytes.iter().map(|b| format!("{}u8", b)).collect();
+    format!("[{}]", bytes.join(", "))
+}
+
+fn bytes_value(value: &Value) -> Result<Vec<u8>, String> {
+    match value {
+        Value::String(text) => match text.strip_prefix("0x") {
+            Some(hex_text) => hex::decode(hex_text).map_err(|e| format!("invalid hex bytes `{}`: {}", text, e)),
+            None => base64::engine::general_purpose::STANDARD
+                .decode(text)
+                .map_err(|e| format!("invalid base64 bytes `{}`: {}", text, e)),
+        },
+        Value::Array(items) => items
+            .iter()
+            .map(|item| item.as_u64().and_then(|b| u8::try_from(b).ok()).ok_or(format!("expected a byte, got {}", item)))
+            .collect(),
+        _ => Err(format!("expected bytes (base64, 0x-prefixed hex or an array of numbers), got {}", value)),
+    }
+}
+
+fn items_literal(items: &[Value], ty: &IdlType, types: &[IdlTypeDef]) -> Result<String, String> {
+    let items = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| literal_value(item, ty, types).map_err(|e| format!("item {}: {}", i, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(items.join(", "))
+}
+
+fn defined_literal(value: &Value, name: &str, generics: &[IdlGenericArg], types: &[IdlTypeDef]) -> Result<String, String> {
+    let type_def = types
+        .iter()
+        .find(|t| t.name == name)
+        .ok_or(format!("type `{}` not found in the IDL", name))?;
+
+    match &instantiate(type_def, generics)? {
+        IdlTypeDefTy::Struct { fields } => fields_literal(value, name, fields.as_ref(), types),
+        IdlTypeDefTy::Enum { variants } => {
+            // `"Variant"` for unit variants, `{ "Variant": <fields> }` otherwise
+            let (variant_name, fields_value) = match value {
+                Value::String(variant_name) => (variant_name.as_str(), &Value::Null),
+                Value::Object(object) if object.len() == 1 => {
+                    let (variant_name, fields_value) = object.iter().next().unwrap();
+                    (variant_name.as_str(), fields_value)
+                }
+                _ => return Err(format!("expected a variant of `{}`, got {}", name, value)),
+            };
+            let variant = variants
+                .iter()
+                .find(|v| v.name == variant_name)
+                .ok_or(format!("`{}` has no variant `{}`", name, variant_name))?;
+
+            fields_literal(fields_value, &format!("{}::{}", name, variant.name), variant.fields.as_ref(), types)
+        }
+        IdlTypeDefTy::Type { alias } => literal_value(value, alias, types),
+    }
+}
+
+fn fields_literal(
+    value: &Value,
+    path: &str,
+    fields: Option<&IdlDefinedFields>,
+    types: &[IdlTypeDef],
+) -> Result<String, String> {
+    match fields {
+        None => Ok(path.to_string()),
+        Some(IdlDefinedFields::Named(fields)) => {
+            let object = value.as_object().ok_or(format!("expected an object for `{}`, got {}", path, value))?;
+            if let Some(unknown) = object.keys().find(|key| !fields.iter().any(|f| &&f.name == key)) {
+                return Err(format!("`{}` has no field `{}`", path, unknown));
+            }
+
+            let fields = fields
+                .iter()
+                .map(|field| {
+                    let field_value = object
+                        .get(&field.name)
+                        .ok_or(format!("missing field `{}` of `{}`", field.name, path))?;
+                    let literal = literal_value(field_value, &field.ty, types)
+                        .map_err(|e| format!("field `{}`: {}", field.name, e))?;
+                    Ok(format!("{}: {}", field.name, literal))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok(format!("{} {{ {} }}", path, fields.join(", ")))
+        }
+        Some(IdlDefinedFields::Tuple(fields)) => {
+            let items = value.as_array().ok_or(format!("expected an array for `{}`, got {}", path, value))?;
+            if items.len() != fields.len() {
+                return Err(format!("expected {} fields for `{}`, got {}", fields.len(), path, items.len()));
+            }
+
+            let fields = items
+                .iter()
+                .zip(fields)
+                .enumerate()
+                .map(|(i, (item, ty))| literal_value(item, ty, types).map_err(|e| format!("field {}: {}", i, e)))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(format!("{}({})", path, fields.join(", ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ty(ty: Value) -> IdlType {
+        serde_json::from_value(ty).unwrap()
+    }
+
+    fn types() -> Vec<IdlTypeDef> {
+        serde_json::from_value(json!([
+            {
+                "name": "Config",
+                "type": { "kind": "struct", "fields": [
+                    { "name": "fee_bps", "type": "u16" },
+                    { "name": "mode", "type": { "defined": { "name": "Mode" } } }
+                ] }
+            },
+            {
+                "name": "Mode",
+                "type": { "kind": "enum", "variants": [
+                    { "name": "Off" },
+                    { "name": "Fixed", "fields": [{ "name": "rate", "type": "u8" }] },
+                    { "name": "Pair", "fields": ["u8", "u16"] }
+                ] }
+            },
+            {
+                "name": "Wrapper",
+                "generics": [{ "kind": "type", "name": "T" }],
+                "type": { "kind": "struct", "fields": [{ "name": "inner", "type": { "generic": "T" } }] }
+            }
+        ]))
+        .unwrap()
+    }
+
+    fn literal(value: Value, idl_type: Value) -> Result<String, String> {
+        literal_value(&value, &ty(idl_type), &types())
+    }
+
+    fn deserialized(bytes: [u8; 32]) -> String {
+        format!("AnchorDeserialize::deserialize(&mut &{}[..]).unwrap()", byte_array(&bytes))
+    }
+
+    #[test]
+    fn integers_are_suffixed_and_range_checked() {
+        assert_eq!(literal(json!(255), json!("u8")).unwrap(), "255u8");
+        assert_eq!(literal(json!(-5), json!("i64")).unwrap(), "-5i64");
+        assert_eq!(
+            literal(json!("340282366920938463463374607431768211455"), json!("u128")).unwrap(),
+            "340282366920938463463374607431768211455u128"
+        );
+        assert_eq!(literal(json!(256), json!("u8")).unwrap_err(), "expected u8, got 256");
+        assert!(literal(json!(-1), json!("u32")).is_err());
+        assert!(literal(json!(true), json!("u64")).is_err());
+        assert_eq!(literal(json!(1.5), json!("f64")).unwrap(), "1.5f64");
+    }
+
+    #[test]
+    fn integers_of_256_bits_are_their_little_endian_bytes() {
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        assert_eq!(literal(json!(1), json!("u256")).unwrap(), deserialized(one));
+        assert_eq!(
+            literal(json!("115792089237316195423570985008687907853269984665640564039457584007913129639935"), json!("u256")).unwrap(),
+            deserialized([0xff; 32])
+        );
+        assert_eq!(literal(json!("-1"), json!("i256")).unwrap(), deserialized([0xff; 32]));
+
+        let mut min = [0u8; 32];
+        min[31] = 0x80;
+        assert_eq!(
+            literal(json!("-57896044618658097711785492504343953926634992332820282019728792003956564819968"), json!("i256")).unwrap(),
+            deserialized(min)
+        );
+        let mut max = [0xff; 32];
+        max[31] = 0x7f;
+        assert_eq!(
+            literal(json!("57896044618658097711785492504343953926634992332820282019728792003956564819967"), json!("i256")).unwrap(),
+            deserialized(max)
+        );
+
+        // One past the range, and negative unsigned values
+        assert!(literal(json!("115792089237316195423570985008687907853269984665640564039457584007913129639936"), json!("u256")).is_err());
+        assert!(literal(json!("57896044618658097711785492504343953926634992332820282019728792003956564819968"), json!("i256")).is_err());
+        assert!(literal(json!("-1"), json!("u256")).is_err());
+        assert!(literal(json!("0x10"), json!("u256")).is_err());
+    }
+
+    #[test]
+    fn pubkeys_and_bytes_are_decoded() {
+        assert_eq!(
+            literal(json!("11111111111111111111111111111111"), json!("pubkey")).unwrap(),
+            format!("Pubkey::new_from_array({})", byte_array(&[0; 32]))
+        );
+        assert!(literal(json!("not a key"), json!("pubkey")).is_err());
+
+        assert_eq!(literal(json!("dGVzdA=="), json!("bytes")).unwrap(), "[116u8, 101u8, 115u8, 116u8].to_vec()");
+        assert_eq!(literal(json!("0xdead"), json!("bytes")).unwrap(), "[222u8, 173u8].to_vec()");
+        assert_eq!(literal(json!([1, 2]), json!("bytes")).unwrap(), "[1u8, 2u8].to_vec()");
+        assert!(literal(json!("0xdea"), json!("bytes")).is_err());
+        assert!(literal(json!([300]), json!("bytes")).is_err());
+    }
+
+    #[test]
+    fn containers_hold_the_literals_of_their_items() {
+        assert_eq!(literal(json!(null), json!({ "option": "u8" })).unwrap(), "None");
+        assert_eq!(literal(json!(3), json!({ "option": "u8" })).unwrap(), "Some(3u8)");
+        assert_eq!(literal(json!([1, 2]), json!({ "vec": "u16" })).unwrap(), "vec![1u16, 2u16]");
+        assert_eq!(literal(json!(["a"]), json!({ "array": ["string", 1] })).unwrap(), r#"["a".to_string()]"#);
+        assert_eq!(literal(json!([1]), json!({ "array": ["u8", 2] })).unwrap_err(), "expected 2 items, got 1");
+        assert_eq!(literal(json!([1, -1]), json!({ "vec": "u8" })).unwrap_err(), "item 1: expected u8, got -1");
+    }
+
+    #[test]
+    fn defined_types_are_built_field_by_field() {
+        let config = json!({ "defined": { "name": "Config" } });
+        assert_eq!(
+            literal(json!({ "fee_bps": 25, "mode": { "Fixed": { "rate": 3 } } }), config.clone()).unwrap(),
+            "Config { fee_bps: 25u16, mode: Mode::Fixed { rate: 3u8 } }"
+        );
+        assert_eq!(
+            literal(json!({ "fee_bps": 0, "mode": { "Pair": [4, 500] } }), config.clone()).unwrap(),
+            "Config { fee_bps: 0u16, mode: Mode::Pair(4u8, 500u16) }"
+        );
+        assert_eq!(
+            literal(json!({ "fee_bps": 0, "mode": "Off" }), config.clone()).unwrap(),
+            "Config { fee_bps: 0u16, mode: Mode::Off }"
+        );
+        assert_eq!(
+            literal(json!({ "inner": 7 }), json!({ "defined": { "name": "Wrapper", "generics": [{ "kind": "type", "type": "u32" }] } }))
+                .unwrap(),
+            "Wrapper { inner: 7u32 }"
+        );
+
+        assert_eq!(
+            literal(json!({ "fee_bps": 0, "mode": "Auto" }), config.clone()).unwrap_err(),
+            "field `mode`: `Mode` has no variant `Auto`"
+        );
+        assert_eq!(literal(json!({ "fee": 0 }), config.clone()).unwrap_err(), "`Config` has no field `fee`");
+        assert_eq!(literal(json!({ "fee_bps": 0 }), config).unwrap_err(), "missing field `mode` of `Config`");
+        assert_eq!(
+            literal(json!({}), json!({ "defined": { "name": "Missing" } })).unwrap_err(),
+            "type `Missing` not found in the IDL"
+        );
+    }
+}
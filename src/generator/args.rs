@@ -1,35 +1,268 @@
-use anchor_idl::{IdlInstruction, IdlType};
+use std::collections::HashMap;
+
+use anchor_idl::{IdlArrayLen, IdlConst, IdlDefinedFields, IdlGenericArg, IdlInstruction, IdlType, IdlTypeDef, IdlTypeDefTy};
+use serde_json::Value;
+
+use crate::generator::arg_values::literal_value;
+use crate::generator::constants::length_limit;
+use crate::generator::generics::instantiate;
 
 pub struct InstructionArgCode {
     pub args: Vec<String>,
     pub call_args: Vec<String>,
 }
 
+/// One case of the `--matrix` mode: a boundary value for one argument, the others keep their value
+pub struct ArgCase {
+    pub name: String, // `<boundary>_<argument>`, e.g. `max_amount`
+    pub arg_index: usize,
+    pub value: String,
+}
+
 impl InstructionArgCode {
-    pub fn generate_argument_code(ix: &IdlInstruction) -> InstructionArgCode {
+    /// `values` are the argument values of this instruction from the `--args` file, used instead of the dummy values.
+    /// `case` replaces the value of one argument with a boundary value.
+    pub fn generate_argument_code(
+        ix: &IdlInstruction,
+        types: &[IdlTypeDef],
+        values: Option<&HashMap<String, Value>>,
+        case: Option<&ArgCase>,
+    ) -> Result<InstructionArgCode, String> {
         // === Generate dummy args ===
-        let mut args = vec![];
-        let mut call_args = vec!["ctx".to_string()];
+        let mut names = vec![];
+        let mut decoded = vec![];
+        let mut dummies = vec![];
+        let mut call_args = vec!["__ctx".to_string()];
 
-        for arg in &ix.args {
+        for (index, arg) in ix.args.iter().enumerate() {
             let arg_name = &arg.name;
-            let dummy = match &arg.ty {
-                IdlType::U8 => "0u8".to_string(),
-                IdlType::U64 => "0u64".to_string(),
-                IdlType::Bool => "false".to_string(),
-                IdlType::String => r#""test".to_string()"#.to_string(),
-                IdlType::Pubkey => "Pubkey::new_unique()".to_string(),
-                IdlType::Array(_, n) => format!("[0u8; {:?}]", n),
-                _ => format!(
-                    "/* unsupported arg type: {:?} */ Default::default()",
-                    arg.ty
-                ),
+            let given = values.and_then(|values| values.get(arg_name));
+            let dummy = match (case.filter(|case| case.arg_index == index), given) {
+                (Some(case), _) => case.value.clone(),
+                (None, Some(value)) => literal_value(value, &arg.ty, types)
+                    .map_err(|e| format!("instruction `{}`, argument `{}`: {}", ix.name, arg_name, e))?,
+                (None, None) => Self::dummy_value(&arg.ty, types).unwrap_or_else(|| {
+                    format!(
+                        "/* unsupported arg type: {:?} */ Default::default()",
+                        arg.ty
+                    )
+                }),
             };
 
-            args.push(format!("let {arg_name} = {dummy};"));
+            // The argument types are inferred from the handler signature (the `instruction::<Ix>` struct with `--entrypoint`),
+            // so Borsh can decode them without naming them
+            decoded.push(format!(
+                r#"AnchorDeserialize::deserialize(&mut __data).map_err(|e| format!("invalid `{arg_name}`: {{}}", e))?"#
+            ));
+            dummies.push(dummy);
+            names.push(arg_name.to_string());
             call_args.push(arg_name.to_string());
         }
 
-        InstructionArgCode { args, call_args }
+        // Arguments given at runtime (REPL) arrive Borsh encoded, otherwise the dummy values are used
+        let args = if names.is_empty() {
+            vec!["let _ = __args;".to_string()]
+        } else {
+            vec![
+                format!("let ({},) = match __args {{", names.join(", ")),
+                format!("    Some(mut __data) => ({},),", decoded.join(", ")),
+                format!("    None => ({},),", dummies.join(", ")),
+                "};".to_string(),
+            ]
+        };
+
+        Ok(InstructionArgCode { args, call_args })
+    }
+
+    /// The `--matrix` cases of an instruction, for every argument with boundary values
+    pub fn boundary_cases(ix: &IdlInstruction, types: &[IdlTypeDef], constants: &[IdlConst]) -> Vec<ArgCase> {
+        ix.args
+            .iter()
+            .enumerate()
+            .flat_map(|(arg_index, arg)| {
+                let mut values = Self::boundary_values(&arg.ty, types);
+                if let Some((constant, limit)) = length_limit(&arg.name, constants) {
+                    values.extend(Self::limit_values(&arg.ty, types, limit, &constant.name));
+                }
+
+                values
+                    .into_iter()
+                    .map(move |(boundary, value)| ArgCase {
+                        name: format!("{}_{}", boundary, arg.name),
+                        arg_index,
+                        value,
+                    })
+            })
+            .collect()
+    }
+
+    // Integers: min, max, zero and one; strings, bytes and vectors: empty and long; options: none and some
+    fn boundary_values(ty: &IdlType, types: &[IdlTypeDef]) -> Vec<(&'static str, String)> {
+        let numeric = |rust_type: &str, signed: bool| {
+            let mut values = vec![];
+            if signed {
+                values.push(("min", format!("{rust_type}::MIN")));
+            }
+            values.push(("max", format!("{rust_type}::MAX")));
+            values.push(("zero", format!("0{rust_type}")));
+            values.push(("one", format!("1{rust_type}")));
+            values
+        };
+
+        match ty {
+            IdlType::Bool => vec![("true", "true".to_string()), ("false", "false".to_string())],
+            IdlType::U8 => numeric("u8", false),
+            IdlType::I8 => numeric("i8", true),
+            IdlType::U16 => numeric("u16", false),
+            IdlType::I16 => numeric("i16", true),
+            IdlType::U32 => numeric("u32", false),
+            IdlType::I32 => numeric("i32", true),
+            IdlType::U64 => numeric("u64", false),
+            IdlType::I64 => numeric("i64", true),
+            IdlType::U128 => numeric("u128", false),
+            IdlType::I128 => numeric("i128", true),
+            IdlType::F32 => numeric("f32", true),
+            IdlType::F64 => numeric("f64", true),
+            IdlType::String => vec![
+                ("empty", "String::new()".to_string()),
+                ("long", r#""a".repeat(1024)"#.to_string()),
+            ],
+            IdlType::Bytes => vec![
+                ("empty", "Vec::<u8>::new()".to_string()),
+                ("long", "vec![0xffu8; 1024]".to_string()),
+            ],
+            IdlType::Vec(inner) => {
+                let mut values = vec![("empty", "Vec::new()".to_string())];
+                if let Some(item) = Self::dummy_value(inner, types) {
+                    values.push(("long", format!("(0..256).map(|_| {}).collect::<Vec<_>>()", item)));
+                }
+                values
+            }
+            IdlType::Option(inner) => {
+                let mut values = vec![("none", "None".to_string())];
+                if let Some(item) = Self::dummy_value(inner, types) {
+                    values.push(("some", format!("Some({})", item)));
+                }
+                values
+            }
+            _ => vec![],
+        }
+    }
+
+    // Exactly at and one past the length limit a `MAX_<ARG>_LEN` style constant declares for the argument
+    fn limit_values(ty: &IdlType, types: &[IdlTypeDef], limit: usize, constant: &str) -> Vec<(&'static str, String)> {
+        let sized = |len: String| match ty {
+            IdlType::String => Some(format!(r#""a".repeat({len})"#)),
+            IdlType::Bytes => Some(format!("vec![0xffu8; {len}]")),
+            IdlType::Vec(inner) => {
+                Self::dummy_value(inner, types).map(|item| format!("(0..{len}).map(|_| {item}).collect::<Vec<_>>()"))
+            }
+            _ => None,
+        };
+
+        [
+            ("limit", sized(format!("{limit} /* {constant} */"))),
+            ("over_limit", sized(format!("{limit} /* {constant} */ + 1"))),
+        ]
+            .into_iter()
+            .filter_map(|(boundary, value)| Some((boundary, value?)))
+            .collect()
+    }
+
+    /// Rust expression of the dummy value for an argument type, `None` when the type is not supported
+    ///
+    /// Defined types are looked up in the IDL `types` and built field by field
+    pub fn dummy_value(ty: &IdlType, types: &[IdlTypeDef]) -> Option<String> {
+        let dummy = match ty {
+            IdlType::Bool => "false".to_string(),
+            IdlType::U8 => "0u8".to_string(),
+            IdlType::I8 => "0i8".to_string(),
+            IdlType::U16 => "0u16".to_string(),
+            IdlType::I16 => "0i16".to_string(),
+            IdlType::U32 => "0u32".to_string(),
+            IdlType::I32 => "0i32".to_string(),
+            IdlType::F32 => "0f32".to_string(),
+            IdlType::U64 => "0u64".to_string(),
+            IdlType::I64 => "0i64".to_string(),
+            IdlType::F64 => "0f64".to_string(),
+            IdlType::U128 => "0u128".to_string(),
+            IdlType::I128 => "0i128".to_string(),
+            // Rust has no native 256-bit integer, the program's own type is inferred from the handler signature
+            IdlType::U256 | IdlType::I256 => "Default::default()".to_string(),
+            IdlType::Bytes => "b\"test\".to_vec()".to_string(),
+            IdlType::String => r#""test".to_string()"#.to_string(),
+            IdlType::Pubkey => "Pubkey::new_unique()".to_string(),
+            IdlType::Option(inner) => format!("Some({})", Self::dummy_value(inner, types)?),
+            IdlType::Vec(inner) => format!("vec![{}]", Self::dummy_value(inner, types)?),
+            IdlType::Array(inner, len) => {
+                let item = Self::dummy_value(inner, types)?;
+                match len {
+                    IdlArrayLen::Value(n) if Self::is_copy_literal(inner) => format!("[{}; {}]", item, n),
+                    // Repeat expressions need `Copy`, the length comes from the handler signature
+                    _ => format!("std::array::from_fn(|_| {})", item),
+                }
+            }
+            IdlType::Defined { name, generics } => return Self::defined_value(name, generics, types),
+            _ => return None,
+        };
+
+        Some(dummy)
+    }
+
+    fn is_copy_literal(ty: &IdlType) -> bool {
+        match ty {
+            IdlType::Array(inner, IdlArrayLen::Value(_)) => Self::is_copy_literal(inner),
+            IdlType::Bool
+            | IdlType::U8
+            | IdlType::I8
+            | IdlType::U16
+            | IdlType::I16
+            | IdlType::U32
+            | IdlType::I32
+            | IdlType::F32
+            | IdlType::U64
+            | IdlType::I64
+            | IdlType::F64
+            | IdlType::U128
+            | IdlType::I128 => true,
+            _ => false,
+        }
+    }
+
+    // Struct literal with every field set, the first variant for enums
+    fn defined_value(name: &str, generics: &[IdlGenericArg], types: &[IdlTypeDef]) -> Option<String> {
+        let type_def = types.iter().find(|t| t.name == name)?;
+
+        match &instantiate(type_def, generics).ok()? {
+            IdlTypeDefTy::Struct { fields } => Self::fields_value(name, fields.as_ref(), types),
+            IdlTypeDefTy::Enum { variants } => {
+                let variant = variants.first()?;
+                Self::fields_value(&format!("{}::{}", name, variant.name), variant.fields.as_ref(), types)
+            }
+            IdlTypeDefTy::Type { alias } => Self::dummy_value(alias, types),
+        }
+    }
+
+    // `Path { a: .., b: .. }`, `Path(.., ..)` or a unit `Path`
+    fn fields_value(path: &str, fields: Option<&IdlDefinedFields>, types: &[IdlTypeDef]) -> Option<String> {
+        let value = match fields {
+            None => path.to_string(),
+            Some(IdlDefinedFields::Named(fields)) => {
+                let fields = fields
+                    .iter()
+                    .map(|field| Some(format!("{}: {}", field.name, Self::dummy_value(&field.ty, types)?)))
+                    .collect::<Option<Vec<_>>>()?;
+                format!("{} {{ {} }}", path, fields.join(", "))
+            }
+            Some(IdlDefinedFields::Tuple(fields)) => {
+                let fields = fields
+                    .iter()
+                    .map(|ty| Self::dummy_value(ty, types))
+                    .collect::<Option<Vec<_>>>()?;
+                format!("{}({})", path, fields.join(", "))
+            }
+        };
+
+        Some(value)
     }
 }
@@ -1,46 +1,242 @@
-use anchor_idl::{IdlInstruction, IdlInstructionAccount, IdlSeed};
+use std::collections::HashMap;
 
-use crate::utils::visit_account_item;
+use anchor_idl::{
+    IdlConst, IdlDefinedFields, IdlField, IdlInstruction, IdlInstructionAccountItem, IdlPda, IdlSeed, IdlType, IdlTypeDef,
+    IdlTypeDefTy,
+};
+
+use crate::generator::accounts::composite_struct_name;
+use crate::generator::constants::seed_constant;
+use crate::generator::token::associated_token;
+use crate::utils::{binding_name, idl_type_to_string, scoped_path};
 
 pub struct InstructionBumpsCode {
     pub bump_fields: Vec<String>,
 }
 
 impl InstructionBumpsCode {
-    pub fn generate_bumps_code(ix: &IdlInstruction) -> InstructionBumpsCode {
+    /// Fails when a seed has no expression, e.g. an argument seed of an unsupported type
+    pub fn generate_bumps_code(
+        ix: &IdlInstruction,
+        types: &[IdlTypeDef],
+        constants: &[IdlConst],
+        account_map: &HashMap<String, String>,
+    ) -> Result<InstructionBumpsCode, String> {
+        let bump_fields = Self::group_bump_fields(ix, &ix.accounts, types, constants, account_map, "")
+            .map_err(|e| format!("instruction `{}`, {}", ix.name, e))?;
+
+        Ok(InstructionBumpsCode { bump_fields })
+    }
+
+    // The Pda derive logic, a composite group gets its own nested `<Struct>Bumps` like Anchor generates it.
+    // `scope` is the path of the group, its account seeds refer to the accounts of the group
+    fn group_bump_fields(
+        ix: &IdlInstruction,
+        items: &[IdlInstructionAccountItem],
+        types: &[IdlTypeDef],
+        constants: &[IdlConst],
+        account_map: &HashMap<String, String>,
+        scope: &str,
+    ) -> Result<Vec<String>, String> {
         let mut bump_fields = vec![];
 
-        // The Pda derive logic
-        for acc in &ix.accounts {
-            let account: &IdlInstructionAccount = visit_account_item(acc).unwrap();
-            let acc_name = &account.name;
-
-            if let Some(pda) = &account.pda {
-                // Create a list of bytes for find_program_address
-                let mut seed_exprs = vec![];
-
-                for seed in &pda.seeds {
-                    match seed {
-                        IdlSeed::Const(seed_const) => {
-                            seed_exprs.push(format!("&{:?}", seed_const.value));
-                        }
-                        IdlSeed::Arg(seed_arg) => {
-                            seed_exprs.push(format!("&{}::to_le_bytes()", seed_arg.path));
-                        }
-                        IdlSeed::Account(seed_account) => {
-                            let account_var = seed_account.path.split('.').next().unwrap();
-                            seed_exprs.push(format!("{}.key().as_ref()", account_var));
-                        }
+        for item in items {
+            match item {
+                IdlInstructionAccountItem::Single(account) => {
+                    let acc_name = &account.name;
+
+                    // `associated_token::` constraints have no bump in the `Bumps` struct
+                    if let Some(pda) = account.pda.as_ref().filter(|_| associated_token(account).is_none()) {
+                        let seed_refs = Self::seed_exprs(pda, constants, scope, &ix.args, types)
+                            .map_err(|e| format!("account `{}`: {}", scoped_path(scope, acc_name), e))?
+                            .join(", ");
+                        // Argument seeds can be longer than the 32 bytes of a seed, e.g. the `--matrix` `long` strings
+                        bump_fields.push(format!(
+                            r#"{acc_name}: Pubkey::try_find_program_address(&[{seed_refs}], &PROGRAM_ID).ok_or("no bump for the seeds of `{}`, a seed is longer than 32 bytes")?.1"#,
+                            scoped_path(scope, acc_name)
+                        ));
                     }
                 }
+                IdlInstructionAccountItem::Composite(group) => {
+                    let nested =
+                        Self::group_bump_fields(ix, &group.accounts, types, constants, account_map, &scoped_path(scope, &group.name))?;
+                    bump_fields.push(format!(
+                        "{}: {}Bumps {{ {} }}",
+                        group.name,
+                        composite_struct_name(&group.name, account_map),
+                        nested.join(", ")
+                    ));
+                }
+            }
+        }
+
+        Ok(bump_fields)
+    }
 
-                let seed_refs = seed_exprs.join(", ");
-                bump_fields.push(format!(
-                    "{acc_name}: Pubkey::find_program_address(&[{seed_refs}], &PROGRAM_ID).1"
-                ));
+    // Create a list of bytes for find_program_address, constant seeds are labeled with their IDL constant.
+    // Account seeds take the key of the binding of the account, `scope` is the path of the group of the PDA.
+    // Argument seeds take the bytes of the argument of the instruction (`args`), which is bound before them
+    pub fn seed_exprs(
+        pda: &IdlPda,
+        constants: &[IdlConst],
+        scope: &str,
+        args: &[IdlField],
+        types: &[IdlTypeDef],
+    ) -> Result<Vec<String>, String> {
+        let mut seed_exprs = vec![];
+
+        for seed in &pda.seeds {
+            match seed {
+                IdlSeed::Const(seed_const) => {
+                    match seed_constant(&seed_const.value, constants) {
+                        Some(constant) => seed_exprs.push(format!("&{:?} /* {} */", seed_const.value, constant.name)),
+                        None => seed_exprs.push(format!("&{:?}", seed_const.value)),
+                    }
+                }
+                IdlSeed::Arg(seed_arg) => {
+                    seed_exprs.push(Self::arg_seed_expr(&seed_arg.path, args, types)?);
+                }
+                IdlSeed::Account(seed_account) => {
+                    let account_var = binding_name(&scoped_path(scope, &seed_account.path));
+                    seed_exprs.push(format!("{}.key().as_ref()", account_var));
+                }
             }
         }
 
-        InstructionBumpsCode { bump_fields }
+        Ok(seed_exprs)
+    }
+
+    // Bytes of an argument seed like the program's `seeds = [...]` takes them, `path` may go into a struct field
+    // (`params.id`)
+    fn arg_seed_expr(path: &str, args: &[IdlField], types: &[IdlTypeDef]) -> Result<String, String> {
+        let mut segments = path.split('.');
+        let arg_name = segments.next().unwrap_or_default();
+        let mut ty = args
+            .iter()
+            .find(|arg| arg.name == arg_name)
+            .map(|arg| arg.ty.clone())
+            .ok_or(format!("argument seed `{}`: the instruction has no argument `{}`", path, arg_name))?;
+
+        for field_name in segments {
+            let IdlType::Defined { name, .. } = &ty else {
+                return Err(format!("argument seed `{}`: `{}` has no fields", path, idl_type_to_string(&ty)));
+            };
+            let field_ty = types.iter().find(|t| &t.name == name).and_then(|type_def| match &type_def.ty {
+                IdlTypeDefTy::Struct { fields: Some(IdlDefinedFields::Named(fields)) } => {
+                    fields.iter().find(|field| field.name == field_name).map(|field| field.ty.clone())
+                }
+                _ => None,
+            });
+            ty = field_ty.ok_or(format!("argument seed `{}`: `{}` has no field `{}`", path, name, field_name))?;
+        }
+
+        let expr = match &ty {
+            IdlType::U8
+            | IdlType::I8
+            | IdlType::U16
+            | IdlType::I16
+            | IdlType::U32
+            | IdlType::I32
+            | IdlType::U64
+            | IdlType::I64
+            | IdlType::U128
+            | IdlType::I128 => format!("&{}.to_le_bytes()", path),
+            IdlType::Bool => format!("&[{} as u8]", path),
+            IdlType::String => format!("{}.as_bytes()", path),
+            IdlType::Pubkey => format!("{}.as_ref()", path),
+            IdlType::Bytes => format!("&{}[..]", path),
+            IdlType::Vec(inner) | IdlType::Array(inner, _) if matches!(**inner, IdlType::U8) => format!("&{}[..]", path),
+            _ => {
+                return Err(format!(
+                    "argument seed `{}` of type `{}` is not supported",
+                    path,
+                    idl_type_to_string(&ty)
+                ))
+            }
+        };
+
+        Ok(expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pda(seeds: serde_json::Value) -> IdlPda {
+        serde_json::from_value(json!({ "seeds": seeds })).unwrap()
+    }
+
+    fn args() -> Vec<IdlField> {
+        serde_json::from_value(json!([
+            { "name": "id", "type": "u64" },
+            { "name": "label", "type": "string" },
+            { "name": "owner", "type": "pubkey" },
+            { "name": "memo", "type": "bytes" },
+            { "name": "params", "type": { "defined": { "name": "Params" } } },
+            { "name": "ratio", "type": "f64" }
+        ]))
+        .unwrap()
+    }
+
+    fn types() -> Vec<IdlTypeDef> {
+        serde_json::from_value(json!([{
+            "name": "Params",
+            "type": { "kind": "struct", "fields": [
+                { "name": "flag", "type": "bool" },
+                { "name": "tag", "type": { "array": ["u8", 4] } }
+            ] }
+        }]))
+        .unwrap()
+    }
+
+    fn seeds(seeds: serde_json::Value) -> Result<Vec<String>, String> {
+        InstructionBumpsCode::seed_exprs(&pda(seeds), &[], "from", &args(), &types())
+    }
+
+    #[test]
+    fn seeds_take_the_bytes_of_constants_accounts_and_arguments() {
+        let exprs = seeds(json!([
+            { "kind": "const", "value": [110, 111] },
+            { "kind": "account", "path": "authority" },
+            { "kind": "arg", "path": "id" },
+            { "kind": "arg", "path": "label" },
+            { "kind": "arg", "path": "owner" },
+            { "kind": "arg", "path": "memo" },
+            { "kind": "arg", "path": "params.flag" },
+            { "kind": "arg", "path": "params.tag" }
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            exprs,
+            [
+                "&[110, 111]",
+                "from_authority.key().as_ref()",
+                "&id.to_le_bytes()",
+                "label.as_bytes()",
+                "owner.as_ref()",
+                "&memo[..]",
+                "&[params.flag as u8]",
+                "&params.tag[..]",
+            ]
+        );
+    }
+
+    #[test]
+    fn argument_seeds_without_bytes_are_errors() {
+        assert_eq!(
+            seeds(json!([{ "kind": "arg", "path": "ratio" }])).unwrap_err(),
+            "argument seed `ratio` of type `f64` is not supported"
+        );
+        assert_eq!(
+            seeds(json!([{ "kind": "arg", "path": "amount" }])).unwrap_err(),
+            "argument seed `amount`: the instruction has no argument `amount`"
+        );
+        assert_eq!(
+            seeds(json!([{ "kind": "arg", "path": "params.mode" }])).unwrap_err(),
+            "argument seed `params.mode`: `Params` has no field `mode`"
+        );
     }
 }
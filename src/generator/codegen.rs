@@ -1,4 +1,4 @@
-use crate::{generate::generate_instruction_function, generator::config::GeneratorConfig};
+use crate::{generate::generate_instruction_function, generator::{args::InstructionArgCode, config::{GenerationOptions, GeneratorConfig}}};
 use anchor_idl::Idl;
 use std::{
     fs::{self, File},
@@ -10,6 +10,9 @@ use std::{
 This is the main logic responsible to generate all the files and code inside them.
 */
 
+// anchor-lang the wrapper crate is built against, the program has to use the same version
+pub const ANCHOR_LANG_VERSION: &str = "0.31.1";
+
 pub struct CodeGenerator<'a> {
     pub idl: &'a Idl,
     pub config: GeneratorConfig<'a>,
@@ -22,20 +25,22 @@ impl<'a> CodeGenerator<'a> {
         crate_path: &'a str,
         out_dir: PathBuf,
         package: &'a str,
-    ) -> CodeGenerator<'a> {
+        standalone: bool,
+        options: GenerationOptions,
+    ) -> Result<CodeGenerator<'a>, Box<dyn std::error::Error>> {
         let program_name = idl.metadata.name.clone();
         let crate_name = program_name.replace("-", "_");
 
         let src_dir = out_dir.join("src");
-        fs::create_dir_all(&src_dir).expect("Failed to create dir");
+        fs::create_dir_all(&src_dir)?;
 
-        let config = GeneratorConfig::new(crate_path, out_dir, src_dir, package);
+        let config = GeneratorConfig::new(crate_path, out_dir, src_dir, package, standalone, options)?;
 
-        CodeGenerator {
+        Ok(CodeGenerator {
             idl,
             config,
             crate_name,
-        }
+        })
     }
 
     pub fn generate_mock_rs(&self) -> Result<(), Box<dyn std::error::Error>> {
@@ -60,8 +65,44 @@ impl<'a> CodeGenerator<'a> {
         Ok(())
     }
 
+    // Copies a template which does not depend on the program as-is into the wrapper's src dir
+    fn write_static_template(&self, template: &str, file_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let template_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/templates").join(template);
+        let contents = fs::read_to_string(template_path).expect("Failed to read a string");
+
+        fs::write(self.config.src_dir.join(file_name), contents)?;
+
+        Ok(())
+    }
+
+    pub fn generate_runner_rs(&self) -> Result<(), Box<dyn std::error::Error>> {
+        // === Write runner.rs (instruction selection from the command line) ===
+        self.write_static_template("runner_template.rs", "runner.rs")
+    }
+
+    pub fn generate_repl_rs(&self) -> Result<(), Box<dyn std::error::Error>> {
+        // === Write repl.rs (interactive mode, `--repl`) ===
+        self.write_static_template("repl_template.rs", "repl.rs")
+    }
+
+    pub fn generate_random_rs(&self) -> Result<(), Box<dyn std::error::Error>> {
+        // === Write random.rs (seeded argument values, `--random-args`) ===
+        self.write_static_template("random_template.rs", "random.rs")
+    }
+
+    pub fn generate_codec_rs(&self) -> Result<(), Box<dyn std::error::Error>> {
+        // === Write codec.rs, the IDL it embeds and its 256-bit integers ===
+        fs::write(self.config.src_dir.join("idl.json"), serde_json::to_string_pretty(self.idl)?)?;
+        self.write_static_template("int256_template.rs", "int256.rs")?;
+        self.write_static_template("codec_template.rs", "codec.rs")
+    }
+
     pub fn generate_cargo_toml(&self) -> Result<(), Box<dyn std::error::Error>> {
         // === Write Cargo.toml ===
+        // The cached wrapper lives under the user's `target/`, an empty `[workspace]` keeps cargo from
+        // treating it as an unlisted member of the user's workspace
+        let workspace = if self.config.standalone { "\n    [workspace]\n" } else { "" };
+
         let cargo_toml = format!(
             r#"[package]
     name = "{crate_name}"
@@ -70,14 +111,16 @@ impl<'a> CodeGenerator<'a> {
 
     [dependencies]
     {crate_name} = {{ path = "{program_path}", package = "{package_name}" }}
-    anchor-lang = "0.31.1"
-    "#,
+    anchor-lang = "{anchor_version}"
+    serde_json = "1"
+    {workspace}"#,
             crate_name = &self.crate_name,
+            anchor_version = ANCHOR_LANG_VERSION,
             program_path = &self.config.program_path,
             package_name = self.config.package_name
         );
 
-        match fs::write(&self.config.out_dir.join("Cargo.toml"), cargo_toml) {
+        match fs::write(self.config.out_dir.join("Cargo.toml"), cargo_toml) {
             Ok(_) => println!("File written successfully"),
             Err(e) => eprintln!("Filed to write Cargo.toml: {}", e),
         }
@@ -87,18 +130,33 @@ impl<'a> CodeGenerator<'a> {
 
     pub fn generate_main_rs(&self) -> Result<(), Box<dyn std::error::Error>> {
         // === Write main.rs stub ===
-        let mut main_rs = File::create(&self.config.src_dir.join("main.rs"))?;
+        let mut main_rs = File::create(self.config.src_dir.join("main.rs"))?;
 
         let mut call_functions = String::new();
-        let mut call_main = String::new();
+        let mut instruction_table = String::new();
 
         for instruction in &self.idl.instructions {
             let func_name = format!("call_{}", instruction.name);
-            let call = format!("    {}();\n", func_name);
-            call_main.push_str(&call);
-            let func = generate_instruction_function(instruction, &self.config.account_map);
+            let entry = format!("            (\"{}\", {}),\n", instruction.name, func_name);
+            instruction_table.push_str(&entry);
+            let func = generate_instruction_function(instruction, self.idl, &self.config, None)?;
 
             call_functions.push_str(&func);
+
+            // `--matrix`: one more function per boundary case, runnable as `<instruction>#<case>`
+            if !self.config.matrix {
+                continue;
+            }
+            for case in InstructionArgCode::boundary_cases(instruction, &self.idl.types, &self.idl.constants) {
+                let entry = format!(
+                    "            (\"{}#{}\", call_{}__{}),\n",
+                    instruction.name, case.name, instruction.name, case.name
+                );
+                instruction_table.push_str(&entry);
+                let func = generate_instruction_function(instruction, self.idl, &self.config, Some(&case))?;
+
+                call_functions.push_str(&func);
+            }
         }
 
         let main_rs_contents = format!(
@@ -112,15 +170,23 @@ impl<'a> CodeGenerator<'a> {
     mod mock;
     use mock::*;
 
+    mod codec;
+    mod int256;
+    mod random;
+    mod repl;
+    mod runner;
+
         fn main() {{
             println!("Native debug wrapper for Anchor program: '{crate_name}'");
-        {call_main}
+            runner::run(&[
+{instruction_table}
+            ]);
         }}
 
         {call_functions}
         "#,
             crate_name = &self.crate_name,
-            call_main = call_main.trim_end(),
+            instruction_table = instruction_table.trim_end(),
             call_functions = call_functions.trim_end(),
         );
 
@@ -1,15 +1,101 @@
+use crate::generator::arg_values::ArgValues;
+use crate::generator::token_config::TokenSettings;
 use crate::scripts::extract_account_struct_map::extract_account_struct_map;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
+    str::FromStr,
 };
 
+/// How the `call_<ix>` functions are generated, from the flags of the commands generating the wrapper
+#[derive(Default)]
+pub struct GenerationOptions {
+    pub arg_values: ArgValues, // from `--args`, replace the dummy argument values
+    pub matrix: bool,          // `--matrix`, also emit a function per boundary value of every argument
+    pub entrypoint: bool,      // `--entrypoint`, go through the program's `entry` instead of calling the handlers
+    pub mocks: MockSettings,   // `--token-program`, `--token-config` and `--clock`
+}
+
+/// Contents of the mocked accounts that are not taken from the IDL
+#[derive(Default)]
+pub struct MockSettings {
+    pub tokens: TokenSettings, // `--token-program` and `--token-config`, owner and contents of the token mocks
+    pub clock: ClockValues,    // `--clock`, fields of the `Clock` sysvar
+}
+
+/// `--clock slot=..,unix_timestamp=..`: fields of the mocked `Clock` sysvar, the others stay 0
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClockValues {
+    pub slot: Option<u64>,
+    pub epoch_start_timestamp: Option<i64>,
+    pub epoch: Option<u64>,
+    pub leader_schedule_epoch: Option<u64>,
+    pub unix_timestamp: Option<i64>,
+}
+
+impl FromStr for ClockValues {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut clock = ClockValues::default();
+        for pair in text.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (field, value) = pair
+                .split_once('=')
+                .ok_or(format!("expected `<field>=<value>`, got `{}`", pair))?;
+            let field = field.trim();
+            let invalid = |e: std::num::ParseIntError| format!("invalid `{}`: {}", field, e);
+            match field {
+                "slot" => clock.slot = Some(value.trim().parse().map_err(invalid)?),
+                "epoch_start_timestamp" => clock.epoch_start_timestamp = Some(value.trim().parse().map_err(invalid)?),
+                "epoch" => clock.epoch = Some(value.trim().parse().map_err(invalid)?),
+                "leader_schedule_epoch" => clock.leader_schedule_epoch = Some(value.trim().parse().map_err(invalid)?),
+                "unix_timestamp" => clock.unix_timestamp = Some(value.trim().parse().map_err(invalid)?),
+                other => {
+                    return Err(format!(
+                        "unknown clock field `{}`, expected slot, epoch_start_timestamp, epoch, leader_schedule_epoch or unix_timestamp",
+                        other
+                    ))
+                }
+            }
+        }
+        Ok(clock)
+    }
+}
+
+impl ClockValues {
+    /// Rust expression of the `Clock` value, e.g. `Clock { slot: 5, ..Clock::default() }`
+    pub fn expression(&self) -> String {
+        let fields: Vec<String> = [
+            ("slot", self.slot.map(|v| v.to_string())),
+            ("epoch_start_timestamp", self.epoch_start_timestamp.map(|v| v.to_string())),
+            ("epoch", self.epoch.map(|v| v.to_string())),
+            ("leader_schedule_epoch", self.leader_schedule_epoch.map(|v| v.to_string())),
+            ("unix_timestamp", self.unix_timestamp.map(|v| v.to_string())),
+        ]
+        .into_iter()
+        .filter_map(|(field, value)| Some(format!("{}: {}", field, value?)))
+        .collect();
+
+        if fields.is_empty() {
+            "Clock::default()".to_string()
+        } else {
+            format!("Clock {{ {}, ..Clock::default() }}", fields.join(", "))
+        }
+    }
+}
+
 pub struct GeneratorConfig<'a> {
     pub program_path: &'a str,
     pub out_dir: PathBuf,
     pub src_dir: PathBuf,
     pub package_name: &'a str,
+    pub standalone: bool, // not a member of the user's workspace (the cached wrapper under `target/debuggen`)
     pub account_map: HashMap<String, String>, // account name -> Account struct name -> Used to derive `DISCRIMINATOR` later when constructing `mock_pda`
+    pub arg_values: ArgValues, // from `--args`, replace the dummy argument values
+    pub matrix: bool, // `--matrix`, also emit a function per boundary value of every argument
+    pub entrypoint: bool, // `--entrypoint`, go through the program's `entry` instead of calling the handlers
+    pub mocks: MockSettings, // `--token-program`, `--token-config` and `--clock`
 }
 
 impl<'a> GeneratorConfig<'a> {
@@ -18,17 +104,60 @@ impl<'a> GeneratorConfig<'a> {
         out_dir: PathBuf,
         src_dir: PathBuf,
         package_name: &'a str,
-    ) -> Self {
+        standalone: bool,
+        options: GenerationOptions,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let crate_src_dir = Path::new(crate_path).join("src");
-        let map = extract_account_struct_map(&crate_src_dir)
-            .expect("Failed to extract account structs from source directory. Maybe you have defined your account structs somewhere else ?");
+        let map = extract_account_struct_map(&crate_src_dir).map_err(|e| {
+            format!("Failed to extract account structs from source directory ({e}). Maybe you have defined your account structs somewhere else ?")
+        })?;
 
-        GeneratorConfig {
+        Ok(GeneratorConfig {
             program_path: crate_path,
             out_dir,
             src_dir,
-            package_name: package_name,
+            package_name,
+            standalone,
             account_map: map,
-        }
+            arg_values: options.arg_values,
+            matrix: options.matrix,
+            entrypoint: options.entrypoint,
+            mocks: options.mocks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_values_parse_every_field() {
+        assert_eq!("".parse::<ClockValues>().unwrap(), ClockValues::default());
+        assert_eq!(
+            "slot=250000000, unix_timestamp = -1,epoch=7,".parse::<ClockValues>().unwrap(),
+            ClockValues { slot: Some(250000000), unix_timestamp: Some(-1), epoch: Some(7), ..ClockValues::default() }
+        );
+        assert_eq!(
+            "epoch_start_timestamp=1700000000,leader_schedule_epoch=8".parse::<ClockValues>().unwrap(),
+            ClockValues { epoch_start_timestamp: Some(1700000000), leader_schedule_epoch: Some(8), ..ClockValues::default() }
+        );
+    }
+
+    #[test]
+    fn clock_values_reject_unknown_fields_and_invalid_numbers() {
+        assert_eq!("slot".parse::<ClockValues>().unwrap_err(), "expected `<field>=<value>`, got `slot`");
+        assert_eq!("slot = -1".parse::<ClockValues>().unwrap_err(), "invalid `slot`: invalid digit found in string");
+        assert!("unix_timestamp=soon".parse::<ClockValues>().is_err());
+        assert!("epochs=1".parse::<ClockValues>().unwrap_err().starts_with("unknown clock field `epochs`"));
+    }
+
+    #[test]
+    fn clock_expression_sets_only_the_given_fields() {
+        assert_eq!(ClockValues::default().expression(), "Clock::default()");
+        assert_eq!(
+            "unix_timestamp=-1,slot=5".parse::<ClockValues>().unwrap().expression(),
+            "Clock { slot: 5, unix_timestamp: -1, ..Clock::default() }"
+        );
     }
 }
@@ -0,0 +1,278 @@
+use std::collections::HashSet;
+
+use anchor_idl::{
+    Idl, IdlArrayLen, IdlConst, IdlDefinedFields, IdlGenericArg, IdlInstruction, IdlSeed, IdlType, IdlTypeDef,
+    IdlTypeDefGeneric, IdlTypeDefTy,
+};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::utils::flatten_accounts;
+
+/*
+IDL `constants` (the program's `#[constant]` items, e.g. `MAX_NAME_LEN` or `SEED_PREFIX`):
+- array lengths and const generic arguments naming a constant are replaced with its value when the IDL is loaded
+- `MAX_<ARG>_LEN` style constants give the `--matrix` length limit of a string, bytes or vector argument
+- constant seeds are labeled with the constant holding the same bytes
+*/
+
+/// A generated value that came from an IDL constant, listed by `inspect` and during generation
+#[derive(Clone, Debug, Serialize)]
+pub struct ConstantUse {
+    pub constant: String,
+    pub value: String,
+    pub owner: String, // the instruction or type the value belongs to
+    pub used_for: String,
+}
+
+/// Numeric value of a constant, `None` for anything that is not an unsigned integer
+pub fn constant_number(constant: &IdlConst) -> Option<usize> {
+    constant.value.trim().replace('_', "").parse().ok()
+}
+
+/// Bytes of a string (`"\"seed\""`), byte string (`b"seed"`) or byte array (`[115, 101]`) constant
+pub fn constant_bytes(constant: &IdlConst) -> Option<Vec<u8>> {
+    let value = constant.value.trim();
+    match serde_json::from_str(value.strip_prefix('b').unwrap_or(value)).ok()? {
+        Value::String(text) => Some(text.into_bytes()),
+        Value::Array(items) => items.iter().map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok())).collect(),
+        _ => None,
+    }
+}
+
+/// The constant a constant seed was written with, found by its bytes
+pub fn seed_constant<'a>(bytes: &[u8], constants: &'a [IdlConst]) -> Option<&'a IdlConst> {
+    constants
+        .iter()
+        .find(|constant| constant_bytes(constant).is_some_and(|value| value == bytes))
+}
+
+/// Length limit of an argument from a `MAX_<ARG>_LEN`, `MAX_<ARG>`, `<ARG>_MAX_LEN`, ... constant
+pub fn length_limit<'a>(arg_name: &str, constants: &'a [IdlConst]) -> Option<(&'a IdlConst, usize)> {
+    // camelCase names of older IDLs become `CAMEL_CASE` as well
+    let mut name = String::new();
+    for c in arg_name.chars() {
+        if c.is_ascii_uppercase() && !name.is_empty() {
+            name.push('_');
+        }
+        name.push(c.to_ascii_uppercase());
+    }
+
+    let candidates = [
+        format!("MAX_{name}_LEN"),
+        format!("MAX_{name}_LENGTH"),
+        format!("MAX_{name}_SIZE"),
+        format!("MAX_{name}"),
+        format!("{name}_MAX_LEN"),
+        format!("{name}_MAX_LENGTH"),
+        format!("{name}_MAX_SIZE"),
+        format!("{name}_MAX"),
+    ];
+    candidates
+        .iter()
+        .find_map(|candidate| constants.iter().find(|constant| &constant.name == candidate))
+        .and_then(|constant| Some((constant, constant_number(constant)?)))
+}
+
+/// Replaces array lengths and const generic arguments naming a constant with its value, e.g. `[u8; MAX_LEN]`
+pub fn resolve_array_lengths(idl: &mut Idl) -> Vec<ConstantUse> {
+    let constants = idl.constants.clone();
+    let mut uses = vec![];
+
+    for ix in &mut idl.instructions {
+        for arg in &mut ix.args {
+            let used_for = format!("argument `{}`", arg.name);
+            resolve_type(&mut arg.ty, &constants, &[], &ix.name, &used_for, &mut uses);
+        }
+    }
+
+    for type_def in &mut idl.types {
+        // A generic parameter of the type shadows a constant of the same name
+        let params: Vec<String> = type_def
+            .generics
+            .iter()
+            .map(|param| match param {
+                IdlTypeDefGeneric::Type { name } | IdlTypeDefGeneric::Const { name, .. } => name.clone(),
+            })
+            .collect();
+        let owner = type_def.name.clone();
+
+        match &mut type_def.ty {
+            IdlTypeDefTy::Struct { fields } => {
+                resolve_fields(fields.as_mut(), &constants, &params, &owner, "", &mut uses)
+            }
+            IdlTypeDefTy::Enum { variants } => {
+                for variant in variants {
+                    let prefix = format!("{}::", variant.name);
+                    resolve_fields(variant.fields.as_mut(), &constants, &params, &owner, &prefix, &mut uses);
+                }
+            }
+            IdlTypeDefTy::Type { alias } => resolve_type(alias, &constants, &params, &owner, &format!("alias `{}`", owner), &mut uses),
+        }
+    }
+
+    uses
+}
+
+fn resolve_fields(
+    fields: Option<&mut IdlDefinedFields>,
+    constants: &[IdlConst],
+    params: &[String],
+    owner: &str,
+    prefix: &str,
+    uses: &mut Vec<ConstantUse>,
+) {
+    match fields {
+        None => {}
+        Some(IdlDefinedFields::Named(fields)) => {
+            for field in fields {
+                let used_for = format!("field `{}{}` of `{}`", prefix, field.name, owner);
+                resolve_type(&mut field.ty, constants, params, owner, &used_for, uses);
+            }
+        }
+        Some(IdlDefinedFields::Tuple(types)) => {
+            for (i, ty) in types.iter_mut().enumerate() {
+                let used_for = format!("field `{}{}` of `{}`", prefix, i, owner);
+                resolve_type(ty, constants, params, owner, &used_for, uses);
+            }
+        }
+    }
+}
+
+fn resolve_type(
+    ty: &mut IdlType,
+    constants: &[IdlConst],
+    params: &[String],
+    owner: &str,
+    used_for: &str,
+    uses: &mut Vec<ConstantUse>,
+) {
+    let mut resolve = |name: &str, what: &str| {
+        if params.iter().any(|param| param == name) {
+            return None;
+        }
+        let constant = constants.iter().find(|constant| constant.name == name)?;
+        let value = constant_number(constant)?;
+        uses.push(ConstantUse {
+            constant: constant.name.clone(),
+            value: value.to_string(),
+            owner: owner.to_string(),
+            used_for: format!("{} of {}", what, used_for),
+        });
+        Some(value)
+    };
+
+    match ty {
+        IdlType::Array(_, len) => {
+            if let IdlArrayLen::Generic(name) = len
+                && let Some(value) = resolve(name, "array length")
+            {
+                *len = IdlArrayLen::Value(value);
+            }
+        }
+        IdlType::Defined { generics, .. } => {
+            for arg in generics.iter_mut() {
+                if let IdlGenericArg::Const { value } = arg
+                    && let Some(resolved) = resolve(value, "generic argument")
+                {
+                    *value = resolved.to_string();
+                }
+            }
+        }
+        _ => {}
+    }
+
+    match ty {
+        IdlType::Option(inner) | IdlType::Vec(inner) | IdlType::Array(inner, _) => {
+            resolve_type(inner, constants, params, owner, used_for, uses)
+        }
+        IdlType::Defined { generics, .. } => {
+            for arg in generics {
+                if let IdlGenericArg::Type { ty } = arg {
+                    resolve_type(ty, constants, params, owner, used_for, uses);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Names of the types the arguments of `ix` use, directly or through other types
+pub fn referenced_types(ix: &IdlInstruction, types: &[IdlTypeDef]) -> HashSet<String> {
+    fn visit(ty: &IdlType, types: &[IdlTypeDef], seen: &mut HashSet<String>) {
+        match ty {
+            IdlType::Option(inner) | IdlType::Vec(inner) | IdlType::Array(inner, _) => visit(inner, types, seen),
+            IdlType::Defined { name, generics } => {
+                for arg in generics {
+                    if let IdlGenericArg::Type { ty } = arg {
+                        visit(ty, types, seen);
+                    }
+                }
+                if !seen.insert(name.clone()) {
+                    return;
+                }
+                let Some(type_def) = types.iter().find(|t| &t.name == name) else {
+                    return;
+                };
+                let fields: Vec<&IdlDefinedFields> = match &type_def.ty {
+                    IdlTypeDefTy::Struct { fields } => fields.iter().collect(),
+                    IdlTypeDefTy::Enum { variants } => variants.iter().filter_map(|v| v.fields.as_ref()).collect(),
+                    IdlTypeDefTy::Type { alias } => return visit(alias, types, seen),
+                };
+                for fields in fields {
+                    match fields {
+                        IdlDefinedFields::Named(fields) => fields.iter().for_each(|f| visit(&f.ty, types, seen)),
+                        IdlDefinedFields::Tuple(tys) => tys.iter().for_each(|ty| visit(ty, types, seen)),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for arg in &ix.args {
+        visit(&arg.ty, types, &mut seen);
+    }
+    seen
+}
+
+/// Every constant feeding a value generated for `ix`: resolved array lengths (`array_uses`, from
+/// `resolve_array_lengths`) of its arguments and their types, length limits and constant seeds
+pub fn instruction_constant_uses(ix: &IdlInstruction, idl: &Idl, array_uses: &[ConstantUse]) -> Vec<ConstantUse> {
+    let types = referenced_types(ix, &idl.types);
+    let mut uses: Vec<ConstantUse> = array_uses
+        .iter()
+        .filter(|u| u.owner == ix.name || types.contains(&u.owner))
+        .cloned()
+        .collect();
+
+    for arg in &ix.args {
+        let sized = matches!(arg.ty, IdlType::String | IdlType::Bytes | IdlType::Vec(_));
+        if let Some((constant, limit)) = length_limit(&arg.name, &idl.constants).filter(|_| sized) {
+            uses.push(ConstantUse {
+                constant: constant.name.clone(),
+                value: limit.to_string(),
+                owner: ix.name.clone(),
+                used_for: format!("length limit of argument `{}` (--matrix `limit_{}`, `over_limit_{}`)", arg.name, arg.name, arg.name),
+            });
+        }
+    }
+
+    for account in flatten_accounts(&ix.accounts) {
+        for seed in account.pda.iter().flat_map(|pda| &pda.seeds) {
+            if let IdlSeed::Const(seed) = seed
+                && let Some(constant) = seed_constant(&seed.value, &idl.constants)
+            {
+                uses.push(ConstantUse {
+                    constant: constant.name.clone(),
+                    value: constant.value.clone(),
+                    owner: ix.name.clone(),
+                    used_for: format!("seed of account `{}`", account.name),
+                });
+            }
+        }
+    }
+
+    uses
+}
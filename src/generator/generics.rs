@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use anchor_idl::{
+    IdlArrayLen, IdlDefinedFields, IdlEnumVariant, IdlField, IdlGenericArg, IdlType, IdlTypeDef, IdlTypeDefGeneric,
+    IdlTypeDefTy,
+};
+
+/*
+Generic IDL types (`Wrapper<T, N>`): the body of the type definition with the generic arguments of the use site
+substituted, so argument values and account layouts are produced from concrete types only.
+*/
+
+/// The definition of `type_def` with `generics` (from `IdlType::Defined`) in place of its generic parameters
+pub fn instantiate(type_def: &IdlTypeDef, generics: &[IdlGenericArg]) -> Result<IdlTypeDefTy, String> {
+    if type_def.generics.is_empty() {
+        return Ok(type_def.ty.clone());
+    }
+    if type_def.generics.len() != generics.len() {
+        return Err(format!(
+            "`{}` takes {} generic arguments, {} given",
+            type_def.name,
+            type_def.generics.len(),
+            generics.len()
+        ));
+    }
+
+    let params: HashMap<&str, &IdlGenericArg> = type_def
+        .generics
+        .iter()
+        .map(|param| match param {
+            IdlTypeDefGeneric::Type { name } | IdlTypeDefGeneric::Const { name, .. } => name.as_str(),
+        })
+        .zip(generics)
+        .collect();
+
+    Ok(match &type_def.ty {
+        IdlTypeDefTy::Struct { fields } => IdlTypeDefTy::Struct {
+            fields: fields.as_ref().map(|f| substitute_fields(f, &params)).transpose()?,
+        },
+        IdlTypeDefTy::Enum { variants } => IdlTypeDefTy::Enum {
+            variants: variants
+                .iter()
+                .map(|variant| {
+                    Ok(IdlEnumVariant {
+                        name: variant.name.clone(),
+                        fields: variant.fields.as_ref().map(|f| substitute_fields(f, &params)).transpose()?,
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+        },
+        IdlTypeDefTy::Type { alias } => IdlTypeDefTy::Type {
+            alias: substitute(alias, &params)?,
+        },
+    })
+}
+
+fn substitute_fields(fields: &IdlDefinedFields, params: &HashMap<&str, &IdlGenericArg>) -> Result<IdlDefinedFields, String> {
+    Ok(match fields {
+        IdlDefinedFields::Named(fields) => IdlDefinedFields::Named(
+            fields
+                .iter()
+                .map(|field| {
+                    Ok(IdlField {
+                        ty: substitute(&field.ty, params)?,
+                        ..field.clone()
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+        ),
+        IdlDefinedFields::Tuple(types) => {
+            IdlDefinedFields::Tuple(types.iter().map(|ty| substitute(ty, params)).collect::<Result<_, _>>()?)
+        }
+    })
+}
+
+fn substitute(ty: &IdlType, params: &HashMap<&str, &IdlGenericArg>) -> Result<IdlType, String> {
+    Ok(match ty {
+        IdlType::Generic(name) => match params.get(name.as_str()) {
+            Some(IdlGenericArg::Type { ty }) => ty.clone(),
+            Some(IdlGenericArg::Const { value }) => {
+                return Err(format!("generic `{}` is a const (`{}`), expected a type", name, value));
+            }
+            None => return Err(format!("unknown generic `{}`", name)),
+        },
+        IdlType::Option(inner) => IdlType::Option(Box::new(substitute(inner, params)?)),
+        IdlType::Vec(inner) => IdlType::Vec(Box::new(substitute(inner, params)?)),
+        IdlType::Array(inner, len) => {
+            let len = match len {
+                IdlArrayLen::Generic(name) => match params.get(name.as_str()) {
+                    Some(IdlGenericArg::Const { value }) => IdlArrayLen::Value(
+                        value
+                            .parse()
+                            .map_err(|_| format!("array length `{}` = `{}` is not a number", name, value))?,
+                    ),
+                    _ => return Err(format!("array length `{}` is not a const generic argument", name)),
+                },
+                IdlArrayLen::Value(n) => IdlArrayLen::Value(*n),
+            };
+            IdlType::Array(Box::new(substitute(inner, params)?), len)
+        }
+        IdlType::Defined { name, generics } => IdlType::Defined {
+            name: name.clone(),
+            generics: generics
+                .iter()
+                .map(|arg| match arg {
+                    IdlGenericArg::Type { ty } => Ok(IdlGenericArg::Type {
+                        ty: substitute(ty, params)?,
+                    }),
+                    // A const parameter forwarded to a nested generic type, e.g. `Inner<N>`
+                    IdlGenericArg::Const { value } => Ok(match params.get(value.as_str()) {
+                        Some(IdlGenericArg::Const { value }) => IdlGenericArg::Const { value: value.clone() },
+                        _ => arg.clone(),
+                    }),
+                })
+                .collect::<Result<_, String>>()?,
+        },
+        other => other.clone(),
+    })
+}
+
+/// Size of the all-zero Borsh encoding of `ty`: empty vectors and strings, `None`, the first enum variant.
+/// `None` when a type cannot be found in the IDL.
+pub fn zeroed_size(ty: &IdlType, types: &[IdlTypeDef]) -> Option<usize> {
+    Some(match ty {
+        IdlType::Bool | IdlType::U8 | IdlType::I8 => 1,
+        IdlType::U16 | IdlType::I16 => 2,
+        IdlType::U32 | IdlType::I32 | IdlType::F32 => 4,
+        IdlType::U64 | IdlType::I64 | IdlType::F64 => 8,
+        IdlType::U128 | IdlType::I128 => 16,
+        IdlType::U256 | IdlType::I256 | IdlType::Pubkey => 32,
+        IdlType::Bytes | IdlType::String | IdlType::Vec(_) => 4,
+        IdlType::Option(_) => 1,
+        IdlType::Array(inner, IdlArrayLen::Value(n)) => n * zeroed_size(inner, types)?,
+        IdlType::Defined { name, generics } => {
+            let type_def = types.iter().find(|t| &t.name == name)?;
+            match instantiate(type_def, generics).ok()? {
+                IdlTypeDefTy::Struct { fields } => fields_size(fields.as_ref(), types)?,
+                IdlTypeDefTy::Enum { variants } => 1 + fields_size(variants.first()?.fields.as_ref(), types)?,
+                IdlTypeDefTy::Type { alias } => zeroed_size(&alias, types)?,
+            }
+        }
+        _ => return None,
+    })
+}
+
+fn fields_size(fields: Option<&IdlDefinedFields>, types: &[IdlTypeDef]) -> Option<usize> {
+    match fields {
+        None => Some(0),
+        Some(IdlDefinedFields::Named(fields)) => fields.iter().map(|f| zeroed_size(&f.ty, types)).sum(),
+        Some(IdlDefinedFields::Tuple(fields)) => fields.iter().map(|ty| zeroed_size(ty, types)).sum(),
+    }
+}
@@ -0,0 +1,74 @@
+/*
+Well-known programs and sysvars, recognized by the `address` the IDL gives to accounts with a fixed key
+(`Program<'info, T>`, `Sysvar<'info, T>`, `#[account(address = ...)]`).
+*/
+
+pub const NATIVE_LOADER_ID: &str = "NativeLoader1111111111111111111111111111111";
+pub const BPF_LOADER_ID: &str = "BPFLoader2111111111111111111111111111111111";
+pub const BPF_LOADER_UPGRADEABLE_ID: &str = "BPFLoaderUpgradeab1e11111111111111111111111";
+pub const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+pub const INSTRUCTIONS_SYSVAR_ID: &str = "Sysvar1nstructions1111111111111111111111111";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KnownKind {
+    /// Executable account owned by the loader that deployed it
+    Program { loader: &'static str },
+    /// Owned by the sysvar program, `layout` is the type in `anchor_lang::prelude` its data is serialized from
+    Sysvar { layout: Option<&'static str> },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct KnownAccount {
+    pub address: &'static str,
+    pub name: &'static str,
+    pub kind: KnownKind,
+}
+
+const fn program(address: &'static str, name: &'static str, loader: &'static str) -> KnownAccount {
+    KnownAccount { address, name, kind: KnownKind::Program { loader } }
+}
+
+const fn sysvar(address: &'static str, name: &'static str, layout: Option<&'static str>) -> KnownAccount {
+    KnownAccount { address, name, kind: KnownKind::Sysvar { layout } }
+}
+
+pub const KNOWN_ACCOUNTS: &[KnownAccount] = &[
+    // Builtin programs
+    program(SYSTEM_PROGRAM_ID, "System Program", NATIVE_LOADER_ID),
+    program("ComputeBudget111111111111111111111111111111", "Compute Budget Program", NATIVE_LOADER_ID),
+    program("AddressLookupTab1e1111111111111111111111111", "Address Lookup Table Program", NATIVE_LOADER_ID),
+    program("Stake11111111111111111111111111111111111111", "Stake Program", NATIVE_LOADER_ID),
+    program("Vote111111111111111111111111111111111111111", "Vote Program", NATIVE_LOADER_ID),
+    program("Config1111111111111111111111111111111111111", "Config Program", NATIVE_LOADER_ID),
+    program("Ed25519SigVerify111111111111111111111111111", "Ed25519 Program", NATIVE_LOADER_ID),
+    program("KeccakSecp256k11111111111111111111111111111", "Secp256k1 Program", NATIVE_LOADER_ID),
+    program(BPF_LOADER_ID, "BPF Loader", NATIVE_LOADER_ID),
+    program(BPF_LOADER_UPGRADEABLE_ID, "BPF Upgradeable Loader", NATIVE_LOADER_ID),
+    // SPL programs
+    program(TOKEN_PROGRAM_ID, "Token Program", BPF_LOADER_ID),
+    program(TOKEN_2022_PROGRAM_ID, "Token-2022 Program", BPF_LOADER_UPGRADEABLE_ID),
+    program(ASSOCIATED_TOKEN_PROGRAM_ID, "Associated Token Program", BPF_LOADER_ID),
+    program("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr", "Memo Program", BPF_LOADER_ID),
+    program("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s", "Token Metadata Program", BPF_LOADER_UPGRADEABLE_ID),
+    // Sysvars
+    sysvar("SysvarC1ock11111111111111111111111111111111", "Clock", Some("Clock")),
+    sysvar("SysvarRent111111111111111111111111111111111", "Rent", Some("Rent")),
+    sysvar("SysvarEpochSchedu1e111111111111111111111111", "Epoch Schedule", Some("EpochSchedule")),
+    sysvar("SysvarS1otHashes111111111111111111111111111", "Slot Hashes", Some("SlotHashes")),
+    sysvar("SysvarS1otHistory11111111111111111111111111", "Slot History", Some("SlotHistory")),
+    sysvar("SysvarStakeHistory1111111111111111111111111", "Stake History", Some("StakeHistory")),
+    // Serialized from the instruction being called instead
+    sysvar(INSTRUCTIONS_SYSVAR_ID, "Instructions", None),
+    // Zeroed
+    sysvar("SysvarEpochRewards1111111111111111111111111", "Epoch Rewards", None),
+    sysvar("SysvarLastRestartS1ot1111111111111111111111", "Last Restart Slot", None),
+    sysvar("SysvarRecentB1ockHashes11111111111111111111", "Recent Blockhashes", None),
+    sysvar("SysvarFees111111111111111111111111111111111", "Fees", None),
+];
+
+pub fn known_account(address: &str) -> Option<&'static KnownAccount> {
+    KNOWN_ACCOUNTS.iter().find(|known| known.address == address)
+}
@@ -1,5 +1,11 @@
 pub mod accounts;
+pub mod arg_values;
 pub mod args;
 pub mod bumps;
 pub mod codegen;
 pub mod config;
+pub mod constants;
+pub mod generics;
+pub mod known_accounts;
+pub mod token;
+pub mod token_config;
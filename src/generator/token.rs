@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use anchor_idl::{IdlInstructionAccount, IdlSeed, IdlTypeDef};
+
+use crate::generator::known_accounts::{ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+
+/*
+SPL Token accounts (`anchor_spl::token::{Mint, TokenAccount}`, `anchor_spl::token_interface` through `InterfaceAccount`):
+their data is the packed `spl_token` state instead of an Anchor discriminator and Borsh layout, and they are owned by
+the token program, Token or Token-2022.
+*/
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TokenKind {
+    Mint,
+    TokenAccount,
+    Program, // `Interface<'info, TokenInterface>`, the IDL has no address for it
+}
+
+/// Token type of an account field, from its type in the program sources; a type the program defines itself is not one
+pub fn token_kind(
+    account: &IdlInstructionAccount,
+    account_map: &HashMap<String, String>,
+    types: &[IdlTypeDef],
+) -> Option<TokenKind> {
+    let type_name = account_map.get(&account.name)?;
+    if types.iter().any(|t| &t.name == type_name) {
+        return None;
+    }
+
+    match type_name.as_str() {
+        "Mint" => Some(TokenKind::Mint),
+        "TokenAccount" => Some(TokenKind::TokenAccount),
+        "Interface<TokenInterface>" => Some(TokenKind::Program),
+        _ => None,
+    }
+}
+
+/// An `associated_token::mint/authority` account, the IDL gives it as a PDA of the associated token program
+/// with the `[wallet, token_program, mint]` seeds
+pub struct AssociatedToken {
+    pub wallet: String, // names of accounts of the same `Accounts` struct
+    pub mint: String,
+    pub token_program: Option<String>, // `None` for `associated_token::token_program = <account>`, the instruction's token program
+}
+
+pub fn associated_token(account: &IdlInstructionAccount) -> Option<AssociatedToken> {
+    let pda = account.pda.as_ref()?;
+    match pda.program.as_ref()? {
+        IdlSeed::Const(program) if base58(&program.value) == ASSOCIATED_TOKEN_PROGRAM_ID => {}
+        _ => return None,
+    }
+
+    // Relative to the `Accounts` struct of the account, see `scoped_path`
+    let account_name = |seed: &IdlSeed| match seed {
+        IdlSeed::Account(seed) => seed.path.split('.').next().map(str::to_string),
+        _ => None,
+    };
+    match pda.seeds.as_slice() {
+        [wallet, token_program, mint] => Some(AssociatedToken {
+            wallet: account_name(wallet)?,
+            mint: account_name(mint)?,
+            token_program: match token_program {
+                IdlSeed::Const(token_program) => Some(base58(&token_program.value)),
+                _ => None,
+            },
+        }),
+        _ => None,
+    }
+}
+
+/// The token program the token accounts of an instruction belong to: Token-2022 when the instruction takes only that one,
+/// Token otherwise
+pub fn instruction_token_program(accounts: &[&IdlInstructionAccount]) -> &'static str {
+    let takes = |program: &str| accounts.iter().any(|account| account.address.as_deref() == Some(program));
+
+    if takes(TOKEN_2022_PROGRAM_ID) && !takes(TOKEN_PROGRAM_ID) {
+        TOKEN_2022_PROGRAM_ID
+    } else {
+        TOKEN_PROGRAM_ID
+    }
+}
+
+fn base58(bytes: &[u8]) -> String {
+    bs58::encode(bytes).into_string()
+}
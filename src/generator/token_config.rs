@@ -0,0 +1,269 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+use anchor_idl::Idl;
+use serde::{Deserialize, Serialize};
+
+use crate::generator::known_accounts::{TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+use crate::generator::token::TokenKind;
+use crate::utils::flatten_accounts;
+
+/*
+Contents of the token mocks given with `--token-config <file.json>`, per account name:
+
+    { "<account>": { "program": "token-2022", "decimals": 9, "amount": 5, "extensions": { "transfer_fee": { ... } } } }
+
+Pubkeys are base58 strings. Extensions are Token-2022 only and packed as TLV entries after the base state, some are for
+mints (`transfer_fee`, `metadata_pointer`, ...), some for token accounts (`immutable_owner`, `memo_transfer`, `cpi_guard`);
+`transfer_fee` on a token account is its withheld amount.
+*/
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum TokenProgram {
+    #[serde(rename = "token")]
+    #[value(name = "token")]
+    Token,
+    #[serde(rename = "token-2022")]
+    #[value(name = "token-2022")]
+    Token2022,
+}
+
+impl TokenProgram {
+    pub fn id(&self) -> &'static str {
+        match self {
+            TokenProgram::Token => TOKEN_PROGRAM_ID,
+            TokenProgram::Token2022 => TOKEN_2022_PROGRAM_ID,
+        }
+    }
+}
+
+/// The `--token-program` owner of every token mock and the `--token-config` accounts
+#[derive(Default)]
+pub struct TokenSettings {
+    pub program: Option<TokenProgram>,
+    pub accounts: HashMap<String, TokenMockConfig>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenMockConfig {
+    pub program: Option<TokenProgram>,
+    pub decimals: Option<u8>,       // mints
+    pub supply: Option<u64>,        // mints
+    pub amount: Option<u64>,        // token accounts
+    pub delegate: Option<String>,   // token accounts, may spend the whole amount
+    #[serde(default)]
+    pub extensions: TokenExtensions,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenExtensions {
+    pub transfer_fee: Option<TransferFee>,
+    pub mint_close_authority: Option<String>,
+    pub permanent_delegate: Option<String>,
+    pub default_account_state: Option<AccountState>,
+    #[serde(default)]
+    pub non_transferable: bool,
+    pub transfer_hook: Option<TransferHook>,
+    pub metadata_pointer: Option<MetadataPointer>,
+    pub token_metadata: Option<TokenMetadata>,
+    #[serde(default)]
+    pub immutable_owner: bool,
+    #[serde(default)]
+    pub memo_transfer: bool, // incoming transfers require a memo
+    #[serde(default)]
+    pub cpi_guard: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransferFee {
+    #[serde(default)]
+    pub basis_points: u16,
+    #[serde(default)]
+    pub maximum_fee: u64,
+    #[serde(default)]
+    pub withheld_amount: u64,
+    pub authority: Option<String>,
+    pub withdraw_withheld_authority: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountState {
+    Initialized,
+    Frozen,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransferHook {
+    pub authority: Option<String>,
+    pub program_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetadataPointer {
+    pub authority: Option<String>,
+    pub metadata_address: Option<String>, // the mint itself when not given
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenMetadata {
+    pub update_authority: Option<String>,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    #[serde(default)]
+    pub additional_metadata: BTreeMap<String, String>,
+}
+
+/// Reads the `--token-config` file, every account in it has to exist in an instruction of the IDL
+pub fn load_token_config(path: &str, idl: &Idl) -> Result<HashMap<String, TokenMockConfig>, Box<dyn std::error::Error>> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Failed to read the token config at {}: {}", path, e))?;
+    let accounts: HashMap<String, TokenMockConfig> = serde_json::from_str(&json).map_err(|e| {
+        format!("Failed to parse the token config at {}, expected {{ \"<account>\": {{ \"program\": .., \"extensions\": {{ .. }} }} }}: {}", path, e)
+    })?;
+
+    for (name, config) in &accounts {
+        let known = idl
+            .instructions
+            .iter()
+            .any(|ix| flatten_accounts(&ix.accounts).iter().any(|account| &account.name == name));
+        if !known {
+            return Err(format!("{}: no instruction has an account `{}`", path, name).into());
+        }
+        for key in config.pubkeys() {
+            check_pubkey(key).map_err(|e| format!("{}: account `{}`: {}", path, name, e))?;
+        }
+    }
+
+    Ok(accounts)
+}
+
+impl TokenMockConfig {
+    fn pubkeys(&self) -> Vec<&String> {
+        let ext = &self.extensions;
+        let fee = ext.transfer_fee.as_ref();
+        let hook = ext.transfer_hook.as_ref();
+        let pointer = ext.metadata_pointer.as_ref();
+        [
+            self.delegate.as_ref(),
+            ext.mint_close_authority.as_ref(),
+            ext.permanent_delegate.as_ref(),
+            fee.and_then(|f| f.authority.as_ref()),
+            fee.and_then(|f| f.withdraw_withheld_authority.as_ref()),
+            hook.and_then(|h| h.authority.as_ref()),
+            hook.and_then(|h| h.program_id.as_ref()),
+            pointer.and_then(|p| p.authority.as_ref()),
+            pointer.and_then(|p| p.metadata_address.as_ref()),
+            ext.token_metadata.as_ref().and_then(|m| m.update_authority.as_ref()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+impl TokenExtensions {
+    /// `TokenExtension` expressions of the mock template for a mint (`mint_key` is its key) or a token account
+    pub fn expressions(&self, kind: TokenKind, mint_key: &str) -> Result<Vec<String>, String> {
+        let key = |key: &Option<String>| key.as_ref().map_or("None".to_string(), |k| format!(r#"Some(pubkey!("{}"))"#, k));
+        let mut exprs = vec![];
+        let mut wrong_kind = vec![];
+
+        let mut mint_only = |name: &'static str, expr: Option<String>| match (expr, kind) {
+            (Some(expr), TokenKind::Mint) => exprs.push(expr),
+            (Some(_), _) => wrong_kind.push(name),
+            (None, _) => {}
+        };
+
+        mint_only("mint_close_authority", self.mint_close_authority.as_ref().map(|a| {
+            format!(r#"TokenExtension::MintCloseAuthority {{ authority: Some(pubkey!("{a}")) }}"#)
+        }));
+        mint_only("default_account_state", self.default_account_state.map(|state| {
+            format!("TokenExtension::DefaultAccountState {{ frozen: {} }}", matches!(state, AccountState::Frozen))
+        }));
+        mint_only("non_transferable", self.non_transferable.then(|| "TokenExtension::NonTransferable".to_string()));
+        mint_only("permanent_delegate", self.permanent_delegate.as_ref().map(|d| {
+            format!(r#"TokenExtension::PermanentDelegate {{ delegate: Some(pubkey!("{d}")) }}"#)
+        }));
+        mint_only("transfer_hook", self.transfer_hook.as_ref().map(|hook| {
+            format!(
+                "TokenExtension::TransferHook {{ authority: {}, program_id: {} }}",
+                key(&hook.authority),
+                key(&hook.program_id)
+            )
+        }));
+        mint_only("metadata_pointer", self.metadata_pointer.as_ref().map(|pointer| {
+            let metadata_address = match &pointer.metadata_address {
+                Some(address) => format!(r#"Some(pubkey!("{address}"))"#),
+                None => format!("Some({mint_key})"),
+            };
+            format!(
+                "TokenExtension::MetadataPointer {{ authority: {}, metadata_address: {} }}",
+                key(&pointer.authority),
+                metadata_address
+            )
+        }));
+        mint_only("token_metadata", self.token_metadata.as_ref().map(|metadata| {
+            let additional: Vec<String> = metadata
+                .additional_metadata
+                .iter()
+                .map(|(k, v)| format!("({:?}.to_string(), {:?}.to_string())", k, v))
+                .collect();
+            format!(
+                "TokenExtension::TokenMetadata {{ update_authority: {}, mint: {}, name: {:?}.to_string(), symbol: {:?}.to_string(), uri: {:?}.to_string(), additional_metadata: vec![{}] }}",
+                key(&metadata.update_authority),
+                mint_key,
+                metadata.name,
+                metadata.symbol,
+                metadata.uri,
+                additional.join(", ")
+            )
+        }));
+
+        let mut account_only = |name: &'static str, set: bool, expr: &str| match (set, kind) {
+            (true, TokenKind::TokenAccount) => exprs.push(expr.to_string()),
+            (true, _) => wrong_kind.push(name),
+            (false, _) => {}
+        };
+        account_only("immutable_owner", self.immutable_owner, "TokenExtension::ImmutableOwner");
+        account_only("memo_transfer", self.memo_transfer, "TokenExtension::MemoTransfer { require_incoming_transfer_memos: true }");
+        account_only("cpi_guard", self.cpi_guard, "TokenExtension::CpiGuard { lock_cpi: true }");
+
+        if let Some(fee) = &self.transfer_fee {
+            exprs.push(match kind {
+                TokenKind::Mint => format!(
+                    "TokenExtension::TransferFeeConfig {{ authority: {}, withdraw_withheld_authority: {}, withheld_amount: {}, maximum_fee: {}, basis_points: {} }}",
+                    key(&fee.authority),
+                    key(&fee.withdraw_withheld_authority),
+                    fee.withheld_amount,
+                    fee.maximum_fee,
+                    fee.basis_points
+                ),
+                _ => format!("TokenExtension::TransferFeeAmount {{ withheld_amount: {} }}", fee.withheld_amount),
+            });
+        }
+
+        match wrong_kind.as_slice() {
+            [] => Ok(exprs),
+            names => Err(format!(
+                "extension(s) `{}` do not apply to a {}",
+                names.join("`, `"),
+                if kind == TokenKind::Mint { "mint" } else { "token account" }
+            )),
+        }
+    }
+}
+
+// The pubkeys of the config are written as `pubkey!("..")`, which only fails when the wrapper is compiled
+fn check_pubkey(text: &str) -> Result<(), String> {
+    match bs58::decode(text).into_vec() {
+        Ok(bytes) if bytes.len() == 32 => Ok(()),
+        _ => Err(format!("`{}` is not a base58 pubkey", text)),
+    }
+}
@@ -0,0 +1,152 @@
+use anchor_idl::{Idl, IdlInstruction};
+use serde::Serialize;
+
+use crate::check::check_instruction;
+use crate::generator::accounts::{AccountMockKind, fixed_address};
+use crate::generator::args::InstructionArgCode;
+use crate::generator::bumps::InstructionBumpsCode;
+use crate::generator::config::GeneratorConfig;
+use crate::generator::constants::{ConstantUse, instruction_constant_uses};
+use crate::generator::known_accounts::known_account;
+use crate::utils::{account_paths, idl_type_to_string};
+
+/*
+Explains what the generator is going to emit for every instruction, without writing anything.
+*/
+
+#[derive(Serialize)]
+pub struct InstructionReport {
+    pub name: String,
+    pub accounts: Vec<AccountReport>,
+    pub args: Vec<ArgReport>,
+    pub constants: Vec<ConstantUse>,
+    pub unsupported: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct AccountReport {
+    pub name: String,
+    pub kind: &'static str,
+    pub account_struct: Option<String>,
+    pub seeds: Vec<String>,
+    pub address: Option<String>,
+    pub known_as: Option<&'static str>, // name of a well-known program or sysvar
+}
+
+#[derive(Serialize)]
+pub struct ArgReport {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub value: String,
+}
+
+/// `array_uses` are the array lengths `resolve_array_lengths` took from the IDL constants when loading it
+pub fn inspect(idl: &Idl, config: &GeneratorConfig, array_uses: &[ConstantUse]) -> Vec<InstructionReport> {
+    idl.instructions
+        .iter()
+        .map(|ix| inspect_instruction(ix, idl, config, array_uses))
+        .collect()
+}
+
+fn inspect_instruction(
+    ix: &IdlInstruction,
+    idl: &Idl,
+    config: &GeneratorConfig,
+    array_uses: &[ConstantUse],
+) -> InstructionReport {
+    let types = &idl.types;
+
+    // Same walk as `InstructionAcountCode::generate_account_code`
+    let accounts = account_paths(&ix.accounts)
+        .into_iter()
+        .map(|(path, account)| {
+            // Account seeds refer to the accounts of the group of the PDA
+            let scope = path.rsplit_once('.').map_or("", |(scope, _)| scope);
+            // Seeds without an expression are listed as unsupported
+            let seeds = account
+                .pda
+                .as_ref()
+                .and_then(|pda| InstructionBumpsCode::seed_exprs(pda, &idl.constants, scope, &ix.args, types).ok());
+            AccountReport {
+                name: path.clone(),
+                kind: AccountMockKind::classify(account, &config.account_map, types).label(),
+                account_struct: config.account_map.get(&account.name).cloned(),
+                seeds: seeds.unwrap_or_default(),
+                address: fixed_address(account).map(str::to_string),
+                known_as: fixed_address(account).and_then(known_account).map(|known| known.name),
+            }
+        })
+        .collect();
+
+    let args = ix
+        .args
+        .iter()
+        .map(|arg| ArgReport {
+            name: arg.name.clone(),
+            ty: idl_type_to_string(&arg.ty),
+            value: InstructionArgCode::dummy_value(&arg.ty, types).unwrap_or("Default::default()".to_string()),
+        })
+        .collect();
+
+    let unsupported = check_instruction(ix, types, &config.account_map)
+        .into_iter()
+        .map(|finding| finding.message)
+        .collect();
+
+    InstructionReport {
+        name: ix.name.clone(),
+        accounts,
+        args,
+        constants: instruction_constant_uses(ix, idl, array_uses),
+        unsupported,
+    }
+}
+
+pub fn print_table(reports: &[InstructionReport]) {
+    for report in reports {
+        println!("== {} ==", report.name);
+
+        println!("  Accounts:");
+        let name_width = report.accounts.iter().map(|a| a.name.len()).max().unwrap_or(0).max(4);
+        println!("    {:<name_width$}  {:<7}  {:<20}  SEEDS / ADDRESS", "NAME", "KIND", "STRUCT");
+        for account in &report.accounts {
+            let seeds = match (&account.address, account.known_as) {
+                (Some(address), Some(known_as)) => format!("{} ({})", address, known_as),
+                (Some(address), None) => address.clone(),
+                _ if account.seeds.is_empty() => "-".to_string(),
+                _ => account.seeds.join(", "),
+            };
+            println!(
+                "    {:<name_width$}  {:<7}  {:<20}  {}",
+                account.name,
+                account.kind,
+                account.account_struct.as_deref().unwrap_or("-"),
+                seeds
+            );
+        }
+
+        if !report.args.is_empty() {
+            println!("  Args:");
+            for arg in &report.args {
+                println!("    {}: {} = {}", arg.name, arg.ty, arg.value);
+            }
+        }
+
+        if !report.constants.is_empty() {
+            println!("  Constants:");
+            for constant in &report.constants {
+                println!("    {} = {}: {}", constant.constant, constant.value, constant.used_for);
+            }
+        }
+
+        if !report.unsupported.is_empty() {
+            println!("  Unsupported:");
+            for item in &report.unsupported {
+                println!("    - {}", item);
+            }
+        }
+
+        println!();
+    }
+}
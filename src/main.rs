@@ -1,14 +1,43 @@
 use anchor_idl::Idl;
 use clap::{Parser, Subcommand};
-use tempfile::tempdir;
-use std::{fs, path::PathBuf};
+use std::{collections::HashMap, fs, path::PathBuf};
 
-use crate::{temp_crate_builder::{build_and_extract_binary, maybe_inject_workspace, prepare_output_path}, utils::{binary_name_from_package, cli_error, inject_workspace_member}};
+use crate::{debugger::LldbOptions, generator::{arg_values::{load_arg_values, ArgValues}, config::{ClockValues, GenerationOptions, GeneratorConfig, MockSettings}, constants::{instruction_constant_uses, resolve_array_lengths, ConstantUse}, token_config::{load_token_config, TokenProgram, TokenSettings}}, scripts::extract_account_struct_map::extract_account_struct_map, temp_crate_builder::{build_and_extract_binary, cached_binary_path, maybe_inject_workspace, prepare_output_path, run_binary}, utils::cli_error};
+mod cache;
+mod check;
+mod debugger;
+mod doctor;
 mod generate;
 pub mod generator;
+mod inspect;
 pub mod scripts;
 mod utils;
 mod temp_crate_builder;
+mod tui;
+mod watch;
+
+// Shared with the wrapper, the `--args` values of 256-bit integers are converted the same way
+#[allow(dead_code)]
+#[path = "templates/int256_template.rs"]
+mod int256;
+
+// The templates are copied into the wrapper crate as-is, they are compiled in here too so their logic can be unit tested.
+// The wrapper is edition 2021, without let chains to collapse their nested `if let`s
+#[cfg(test)]
+#[allow(dead_code, clippy::collapsible_if)]
+#[path = "templates/codec_template.rs"]
+mod codec;
+#[cfg(test)]
+#[allow(dead_code, clippy::collapsible_if)]
+#[path = "templates/random_template.rs"]
+mod random;
+#[cfg(test)]
+#[allow(dead_code, clippy::collapsible_if)]
+mod mock {
+    // Prepended by `generate_mock_rs` in the wrapper
+    const PROGRAM_ID: anchor_lang::prelude::Pubkey = anchor_lang::prelude::Pubkey::new_from_array([7; 32]);
+    include!("templates/mock_template.rs");
+}
 
 #[derive(Parser)]
 #[command(author, version, about)]
@@ -19,68 +48,524 @@ pub struct Args {
 
 #[derive(Subcommand)]
 pub enum Command {
+    /// Generates the debug wrapper crate and builds its binary into `target/debug/<package>`
     Generate {
-        #[arg(long, help = "Package name of the Anchor program (required)")]
-        package: String,
+        #[command(flatten)]
+        wrapper: WrapperArgs,
 
-        #[arg(
-            long,
-            help = "Optional path to the generated IDL .json file. Inferred from --package if not provided."
-        )]
-        idl: Option<String>,
+        #[arg(long, help = "Regenerate, rebuild and rerun whenever the program sources, the IDL or Anchor.toml change")]
+        watch: bool,
+    },
+    /// Generates and builds the debug wrapper, then starts lldb on the resulting binary
+    Debug {
+        #[command(flatten)]
+        wrapper: WrapperArgs,
+
+        #[arg(long, help = "Break at the start of the generated `call_<instruction>` function")]
+        instruction: Option<String>,
 
         #[arg(
             long,
-            help = "Optional path to the Anchor program crate root. Inferred from --package if not provided."
+            help = "Break inside the program handler (of --instruction if given, otherwise of every instruction)"
         )]
-        program_crate_path: Option<String>,
+        break_in_handler: bool,
 
-        #[arg(
-            help = "Optional output directory for the generated wrapper (default: debug-wrapper)"
-        )]
-        out: Option<String>,
+        #[arg(last = true, help = "Extra arguments passed to lldb as-is (after `--`)")]
+        lldb_args: Vec<String>,
     },
+    /// Runs an already generated wrapper with the flags it was generated with, rebuilding it only when the program crate changed
+    Run {
+        #[command(flatten)]
+        wrapper: WrapperArgs,
+
+        #[arg(long, value_delimiter = ',', help = "Run only these instructions")]
+        only: Vec<String>,
+
+        #[arg(long, value_delimiter = ',', help = "Do not run these instructions")]
+        skip: Vec<String>,
+
+        #[arg(long, help = "Draw the argument values from a seeded RNG, failing calls print the seed and their values")]
+        random_args: bool,
+
+        #[arg(long, requires = "random_args", help = "Seed of --random-args (default: from the clock)")]
+        seed: Option<u64>,
+
+        #[arg(long, requires = "random_args", help = "Random argument values drawn per instruction")]
+        iterations: Option<usize>,
+
+        #[arg(long, help = "Regenerate, rebuild and rerun whenever the program sources, the IDL or Anchor.toml change")]
+        watch: bool,
+
+        #[arg(last = true, help = "Extra arguments passed to the debug binary as-is (after `--`)")]
+        bin_args: Vec<String>,
+    },
+    /// Prints, for every instruction, what the generator is going to emit and what it does not support
+    Inspect {
+        #[command(flatten)]
+        program: ProgramArgs,
+
+        #[arg(long, value_enum, default_value = "table", help = "Output format")]
+        format: OutputFormat,
+    },
+    /// Reports every IDL feature the generator does not support, exits non-zero when any of them is an error
+    Check {
+        #[command(flatten)]
+        program: ProgramArgs,
+
+        #[arg(long, value_enum, default_value = "table", help = "Output format")]
+        format: OutputFormat,
+    },
+    /// Terminal UI to browse the instructions, edit their arguments and run or debug them
+    Tui {
+        #[command(flatten)]
+        wrapper: WrapperArgs,
+    },
+    /// Diagnoses the environment (lldb, cargo, workspace layout, IDL, anchor-lang version) and prints a fix for every problem
+    Doctor {
+        #[command(flatten)]
+        program: ProgramArgs,
+    },
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+/// Arguments locating the Anchor program and its IDL
+#[derive(Clone, clap::Args)]
+pub struct ProgramArgs {
+    #[arg(long, help = "Package name of the Anchor program (required)")]
+    pub package: String,
+
+    #[arg(
+        long,
+        help = "Optional path to the generated IDL .json file. Inferred from --package if not provided."
+    )]
+    pub idl: Option<String>,
+
+    #[arg(
+        long,
+        help = "Optional path to the Anchor program crate root. Inferred from --package if not provided."
+    )]
+    pub program_crate_path: Option<String>,
+}
+
+/// Arguments shared by every subcommand that generates the debug wrapper crate
+#[derive(Clone, clap::Args)]
+pub struct WrapperArgs {
+    #[command(flatten)]
+    pub program: ProgramArgs,
+
+    #[arg(
+        help = "Optional output directory for the generated wrapper (default: debug-wrapper)"
+    )]
+    pub out: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "JSON file with the argument values to use instead of the dummy ones: { \"<instruction>\": { \"<argument>\": <value> } }"
+    )]
+    pub args: Option<String>,
+
+    #[arg(
+        long,
+        help = "Also generate boundary value cases (min, max, zero, one, empty, long, none, some) of every argument, runnable as `<instruction>#<case>`"
+    )]
+    pub matrix: bool,
+
+    #[arg(
+        long,
+        help = "Call the program's `entry` with the IDL discriminator and Borsh encoded arguments instead of the handlers, so Anchor's dispatcher checks every account constraint"
+    )]
+    pub entrypoint: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Owner of the mocked mints, token accounts and `Interface<'info, TokenInterface>` programs (default: the token program the instruction takes, Token otherwise)"
+    )]
+    pub token_program: Option<TokenProgram>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "JSON file with the contents of token mocks per account: program, decimals, supply, amount, delegate and Token-2022 extensions"
+    )]
+    pub token_config: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FIELD=VALUE,..",
+        help = "Fields of the mocked `Clock` sysvar: slot, epoch_start_timestamp, epoch, leader_schedule_epoch, unix_timestamp (default: 0)"
+    )]
+    pub clock: Option<ClockValues>,
+
+    #[arg(long, help = "Regenerate and rebuild the cached wrapper even if its inputs did not change")]
+    pub no_cache: bool,
+
+    #[arg(long, help = "Print the path of the generated wrapper sources so they can be inspected")]
+    pub keep_temp: bool,
 }
 
+impl WrapperArgs {
+    fn stored_options(&self, idl_json: &str) -> cache::StoredOptions {
+        cache::StoredOptions {
+            args: self.args.clone(),
+            matrix: self.matrix,
+            entrypoint: self.entrypoint,
+            token_program: self.token_program,
+            token_config: self.token_config.clone(),
+            clock: self.clock.clone(),
+            idl_hash: cache::idl_hash(idl_json),
+        }
+    }
+
+    // The flags a wrapper was generated with, the ones given again take precedence
+    fn with_stored_options(&self, stored: &cache::StoredOptions) -> WrapperArgs {
+        WrapperArgs {
+            args: self.args.clone().or(stored.args.clone()),
+            matrix: self.matrix || stored.matrix,
+            entrypoint: self.entrypoint || stored.entrypoint,
+            token_program: self.token_program.or(stored.token_program),
+            token_config: self.token_config.clone().or(stored.token_config.clone()),
+            clock: self.clock.clone().or(stored.clock.clone()),
+            ..self.clone()
+        }
+    }
+}
+
+/// The result of generating (or reusing) the wrapper crate
+pub struct PreparedWrapper {
+    pub idl: Idl,
+    pub out_path: PathBuf,
+    pub is_ephemeral: bool,
+    // Hash of the cached wrapper inputs, stored once the build succeeded
+    pub cache_hash: Option<String>,
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
 
     match args.command {
-        Command::Generate {
-            package,
-            idl,
-            program_crate_path,
-            out,
+        Command::Generate { wrapper, watch: true } => watch_loop(&wrapper, &[]),
+        Command::Generate { wrapper, watch: false } => {
+            let prepared = prepare_wrapper(&wrapper)?;
+
+            // If using the cached wrapper, build the binary, extract it, and optionally run it
+            if prepared.is_ephemeral {
+                let bin_path = wrapper_binary(&wrapper.program.package, &prepared)?;
+                run_binary(&bin_path, &[])?;
+            }
+        }
+        Command::Debug {
+            wrapper,
+            instruction,
+            break_in_handler,
+            lldb_args,
         } => {
-            let (idl_path, program_crate_path) = match (&idl, &program_crate_path) {
-                (Some(idl), Some(crate_path)) => (idl.clone(), crate_path.clone()),
-                _ => utils::infer_paths(&package)?,
+            let prepared = prepare_wrapper(&wrapper)?;
+
+            if let Some(ix_name) = &instruction
+                && !prepared.idl.instructions.iter().any(|ix| &ix.name == ix_name)
+            {
+                cli_error(format!("Instruction `{}` not found in the IDL", ix_name));
+            }
+
+            // The debugger always needs a binary, so build even when the wrapper lives in the workspace
+            let bin_path = wrapper_binary(&wrapper.program.package, &prepared)?;
+
+            debugger::launch_lldb(
+                &bin_path,
+                &LldbOptions {
+                    program_mod: &prepared.idl.metadata.name,
+                    instruction: instruction.as_deref(),
+                    break_in_handler,
+                    extra_args: &lldb_args,
+                    program_args: &[],
+                },
+            )?;
+        }
+        Command::Run {
+            wrapper,
+            only,
+            skip,
+            random_args,
+            seed,
+            iterations,
+            watch,
+            bin_args,
+        } => {
+            let mut forwarded = vec![];
+            if !only.is_empty() {
+                forwarded.push(format!("--only={}", only.join(",")));
+            }
+            if !skip.is_empty() {
+                forwarded.push(format!("--skip={}", skip.join(",")));
+            }
+            if random_args {
+                forwarded.push("--random-args".to_string());
+            }
+            if let Some(seed) = seed {
+                forwarded.push(format!("--seed={}", seed));
+            }
+            if let Some(iterations) = iterations {
+                forwarded.push(format!("--iterations={}", iterations));
+            }
+            forwarded.extend(bin_args);
+
+            // Regenerating with other flags than `generate` was given would drop e.g. the `--matrix` cases
+            let (out_path, _) = prepare_output_path(&wrapper.out, &wrapper.program.package)?;
+            let wrapper = match cache::load_options(&out_path) {
+                Some(stored) => wrapper.with_stored_options(&stored),
+                None => wrapper,
             };
 
-            let idl_json = fs::read_to_string(&idl_path)
-                .map_err(|e| format!("Failed to read IDL file at {}: {}", idl_path, e))?;
+            if watch {
+                watch_loop(&wrapper, &forwarded);
+            }
+
+            let bin_path = existing_wrapper_binary(&wrapper)?;
+            run_binary(&bin_path, &forwarded)?;
+        }
+        Command::Inspect { program, format } => {
+            let (idl_path, program_crate_path) = resolve_paths(&program)?;
+            let (_, idl, constant_uses) = load_idl(&idl_path)?;
 
-            let idl: Idl = serde_json::from_str(&idl_json)
-                .map_err(|e| format!("Failed to parse IDL JSON at {}: {}", idl_path, e))?;
+            let (out_path, _) = prepare_output_path(&None, &program.package)?;
+            let config = GeneratorConfig::new(&program_crate_path, out_path.clone(), out_path.join("src"), &program.package, true, GenerationOptions::default())?;
 
-            // determine the output path: either user-specified or a temporary one
-            let (out_path, is_ephemeral, _temp_guard) = prepare_output_path(&out)?;
+            let reports = inspect::inspect(&idl, &config, &constant_uses);
+            match format {
+                OutputFormat::Table => inspect::print_table(&reports),
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&reports)?),
+            }
+        }
+        Command::Check { program, format } => {
+            let (idl_path, program_crate_path) = resolve_paths(&program)?;
+            let (_, idl, _) = load_idl(&idl_path)?;
+            let account_map = extract_account_struct_map(&PathBuf::from(program_crate_path).join("src"))?;
 
-            // Inject debug-wrapper into root cargo workspace if not ephemeral
-            maybe_inject_workspace(&out_path, is_ephemeral);
+            let findings = check::check(&idl, &account_map);
+            match format {
+                OutputFormat::Table => check::print_findings(&findings),
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&findings)?),
+            }
 
-            // Generate the debug wrapper crate files
-            if let Err(e) = generate::generate_wrapper(&idl, &program_crate_path, &out_path, &package) {
-                cli_error(e);
+            if findings.iter().any(|f| f.severity == check::Severity::Error) {
+                std::process::exit(1);
             }
+        }
+        Command::Tui { wrapper } => {
+            let prepared = prepare_wrapper(&wrapper)?;
+            let bin_path = wrapper_binary(&wrapper.program.package, &prepared)?;
 
-            // If using temp dir, build the binary, extract it, and optionally run it
-            if is_ephemeral {
-                build_and_extract_binary(&package, &out_path)?;
+            let (_, program_crate_path) = resolve_paths(&wrapper.program)?;
+            let account_map = extract_account_struct_map(&PathBuf::from(program_crate_path).join("src"))?;
+
+            tui::start(&prepared.idl, &account_map, &bin_path)?;
+        }
+        Command::Doctor { program } => {
+            if !doctor::run(&program)? {
+                std::process::exit(1);
             }
         }
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+// Regenerates, rebuilds and reruns on every change, reporting which instructions changed result since the last run
+fn watch_loop(wrapper: &WrapperArgs, bin_args: &[String]) -> ! {
+    let paths = resolve_paths(&wrapper.program)
+        .and_then(|(idl_path, program_crate_path)| watch::watch_paths(&program_crate_path, &idl_path))
+        .unwrap_or_else(|e| cli_error(e));
+
+    let mut previous: Option<watch::RunResults> = None;
+
+    loop {
+        // Taken before regenerating, so a change saved while the wrapper builds or runs triggers the next iteration
+        let snapshot = watch::snapshot(&paths);
+
+        // A half-finished edit should not end the session, so errors are only reported
+        let results = prepare_wrapper(wrapper)
+            .and_then(|prepared| wrapper_binary(&wrapper.program.package, &prepared))
+            .and_then(|bin_path| watch::run_and_collect(&bin_path, bin_args));
+
+        match results {
+            Ok(results) => {
+                if let Some(previous) = &previous {
+                    let changes = watch::diff_results(previous, &results);
+                    if changes.is_empty() {
+                        println!("\n[WATCH] No instruction changed result since the last run");
+                    } else {
+                        println!("\n[WATCH] Results changed since the last run:");
+                        for change in changes {
+                            println!("  {}", change);
+                        }
+                    }
+                }
+                previous = Some(results);
+            }
+            Err(e) => eprintln!("\n[WATCH] anchor-lldb Error: {}", e),
+        }
+
+        println!("[WATCH] Waiting for changes in:");
+        for path in &paths {
+            println!(" -> {}", path.display());
+        }
+        watch::wait_for_change(&paths, &snapshot);
+    }
+}
+
+// Resolves the IDL path and the program crate path, either user-specified or inferred from the package
+fn resolve_paths(program: &ProgramArgs) -> Result<(String, String), Box<dyn std::error::Error>> {
+    match (&program.idl, &program.program_crate_path) {
+        (Some(idl), Some(crate_path)) => Ok((idl.clone(), crate_path.clone())),
+        _ => utils::infer_paths(&program.package),
+    }
+}
+
+// Finds the binary of a previously generated wrapper, rebuilding it only if the program crate changed since
+fn existing_wrapper_binary(wrapper: &WrapperArgs) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if let Some(out) = &wrapper.out {
+        let out_path = PathBuf::from(out);
+        if !out_path.join("Cargo.toml").exists() {
+            return Err(format!(
+                "No generated wrapper found at `{}`, run `anchor-lldb generate --package {} {}` first",
+                out, wrapper.program.package, out
+            )
+            .into());
+        }
+
+        // A wrapper living in the workspace: cargo itself knows whether the program crate changed, the generated code
+        // only has to follow a changed IDL
+        let (idl_path, _) = resolve_paths(&wrapper.program)?;
+        let (idl_json, _, _) = load_idl(&idl_path)?;
+        if cache::load_options(&out_path).is_none_or(|stored| stored.idl_hash == cache::idl_hash(&idl_json)) {
+            return build_and_extract_binary(&wrapper.program.package, &out_path, false);
+        }
+        println!("[INFO] The IDL changed since the wrapper was generated, regenerating it");
+    } else {
+        let cache_dir = cache::cache_dir(&wrapper.program.package)?;
+        if !cache_dir.join("Cargo.toml").exists() && !cached_binary_path(&wrapper.program.package).exists() {
+            return Err(format!(
+                "No generated wrapper found in `target/debuggen`, run `anchor-lldb generate --package {}` first",
+                wrapper.program.package
+            )
+            .into());
+        }
+    }
+
+    // The cache hash tells whether the program crate changed, in which case the wrapper is regenerated
+    let prepared = prepare_wrapper(wrapper)?;
+    wrapper_binary(&wrapper.program.package, &prepared)
+}
+
+// Builds the wrapper, then extracts it. A cached wrapper was not regenerated, cargo's incremental build is then a no-op
+// unless something the cache hash does not cover changed
+fn wrapper_binary(package: &str, prepared: &PreparedWrapper) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let bin_path = build_and_extract_binary(package, &prepared.out_path, prepared.is_ephemeral)?;
+    if let Some(hash) = &prepared.cache_hash {
+        cache::store_hash(&prepared.out_path, hash)?;
+    }
+
+    Ok(bin_path)
+}
+
+// Reads and parses the IDL, the raw JSON is kept for hashing. Array lengths naming an IDL constant are resolved,
+// the constants used for them are returned as well
+fn load_idl(idl_path: &str) -> Result<(String, Idl, Vec<ConstantUse>), Box<dyn std::error::Error>> {
+    let idl_json = fs::read_to_string(idl_path)
+        .map_err(|e| format!("Failed to read IDL file at {}: {}", idl_path, e))?;
+
+    let mut idl: Idl = serde_json::from_str(&idl_json)
+        .map_err(|e| format!("Failed to parse IDL JSON at {}: {}", idl_path, e))?;
+    let constant_uses = resolve_array_lengths(&mut idl);
+
+    Ok((idl_json, idl, constant_uses))
+}
+
+// Loads the IDL, generates the wrapper crate into the output path and returns everything needed to build it
+fn prepare_wrapper(wrapper: &WrapperArgs) -> Result<PreparedWrapper, Box<dyn std::error::Error>> {
+    let (idl_path, program_crate_path) = resolve_paths(&wrapper.program)?;
+
+    let (idl_json, idl, constant_uses) = load_idl(&idl_path)?;
+
+    let (arg_values_json, arg_values) = match &wrapper.args {
+        Some(args_path) => (fs::read_to_string(args_path)?, load_arg_values(args_path, &idl)?),
+        None => (String::new(), ArgValues::new()),
+    };
+    let (token_config_json, token_accounts) = match &wrapper.token_config {
+        Some(config_path) => (fs::read_to_string(config_path)?, load_token_config(config_path, &idl)?),
+        None => (String::new(), HashMap::new()),
+    };
+
+    // determine the output path: either user-specified or the cache dir under `target/debuggen`
+    let (out_path, is_ephemeral) = prepare_output_path(&wrapper.out, &wrapper.program.package)?;
+
+    if wrapper.keep_temp && is_ephemeral {
+        println!("[INFO] Generated wrapper sources:\n -> {}", out_path.display());
+    }
+
+    let cache_hash = if is_ephemeral {
+        let token_program = wrapper.token_program.map_or("", |program| program.id());
+        let clock = format!("{:?}", wrapper.clock);
+        let inputs = [arg_values_json.as_str(), token_config_json.as_str(), token_program, clock.as_str()];
+        Some(cache::wrapper_hash(&idl_json, &inputs, wrapper.matrix, wrapper.entrypoint, &program_crate_path)?)
+    } else {
+        None
+    };
+
+    if let Some(hash) = &cache_hash
+        && !wrapper.no_cache
+        && cache::is_fresh(&out_path, hash, &cached_binary_path(&wrapper.program.package))
+    {
+        println!("[INFO] Inputs unchanged, reusing the cached debug wrapper");
+        return Ok(PreparedWrapper {
+            idl,
+            out_path,
+            is_ephemeral,
+            cache_hash,
+        });
+    }
+
+    // Inject debug-wrapper into root cargo workspace if not ephemeral
+    maybe_inject_workspace(&out_path, is_ephemeral);
+
+    if is_ephemeral {
+        cache::invalidate(&out_path);
+    }
+
+    for ix in &idl.instructions {
+        for constant in instruction_constant_uses(ix, &idl, &constant_uses) {
+            println!("[INFO] {}: {} = {} used for the {}", ix.name, constant.constant, constant.value, constant.used_for);
+        }
+    }
+
+    // Generate the debug wrapper crate files
+    let options = GenerationOptions {
+        arg_values,
+        matrix: wrapper.matrix,
+        entrypoint: wrapper.entrypoint,
+        mocks: MockSettings {
+            tokens: TokenSettings {
+                program: wrapper.token_program,
+                accounts: token_accounts,
+            },
+            clock: wrapper.clock.clone().unwrap_or_default(),
+        },
+    };
+    generate::generate_wrapper(&idl, &program_crate_path, &out_path, &wrapper.program.package, is_ephemeral, options)
+        .map_err(|e| format!("Failed to generate the debug wrapper: {}", e))?;
+    cache::store_options(&out_path, &wrapper.stored_options(&idl_json))?;
+
+    Ok(PreparedWrapper {
+        idl,
+        out_path,
+        is_ephemeral,
+        cache_hash,
+    })
+}
@@ -1,7 +1,11 @@
 use std::fs;
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
 use walkdir::WalkDir;
 
+use quote::ToTokens;
 use syn::{Attribute, Fields, File, Item, ItemStruct, PathArguments, Type, TypePath};
 
 fn has_derive_accounts_attr(attrs: &[Attribute]) -> bool {
@@ -23,28 +27,55 @@ fn has_derive_accounts_attr(attrs: &[Attribute]) -> bool {
 }
 
 /// Extracts a map: `field_name -> account_struct_name`
-/// Example: `counter_acc_bro -> CounterAccount`
+/// Example: `counter_acc_bro -> CounterAccount`, `config -> Wrapper<u64,4>`, `system_program -> Program<System>`, and for
+/// nested `Accounts` structs (composite groups) the struct itself: `transfer -> TokenTransfer`
 pub fn extract_account_struct_map(
     source_dir: &PathBuf,
 ) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
     let mut result = HashMap::new();
 
+    let mut files = vec![];
     for entry in WalkDir::new(source_dir)
         .into_iter()
         .filter_map(Result::ok)
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "rs"))
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
     {
         let path = entry.path();
         let content = fs::read_to_string(path)?;
-        let syntax = syn::parse_file(&content)?;
+        files.push(syn::parse_file(&content)?);
+    }
+
+    // A composite group can be declared in another file than the struct using it
+    let accounts_structs: HashSet<String> = files
+        .iter()
+        .flat_map(|file| &file.items)
+        .filter_map(|item| match item {
+            Item::Struct(item) if has_derive_accounts_attr(&item.attrs) => Some(item.ident.to_string()),
+            _ => None,
+        })
+        .collect();
 
-        extract_from_file(&syntax, &mut result);
+    for file in &files {
+        extract_from_file(file, &accounts_structs, &mut result);
     }
 
     Ok(result)
 }
 
-fn extract_from_file(file: &File, map: &mut HashMap<String, String>) {
+// `Box<Account<'info, T>>`, common for large accounts such as token accounts, is mapped like `Account<'info, T>`
+fn unboxed(ty: &Type) -> &Type {
+    if let Type::Path(TypePath { path, .. }) = ty
+        && let Some(segment) = path.segments.last()
+        && segment.ident == "Box"
+        && let PathArguments::AngleBracketed(args) = &segment.arguments
+        && let Some(syn::GenericArgument::Type(inner)) = args.args.first()
+    {
+        return inner;
+    }
+    ty
+}
+
+fn extract_from_file(file: &File, accounts_structs: &HashSet<String>, map: &mut HashMap<String, String>) {
     for item in &file.items {
         if let Item::Struct(ItemStruct { attrs, fields, .. }) = item {
             if !has_derive_accounts_attr(attrs) {
@@ -57,28 +88,41 @@ fn extract_from_file(file: &File, map: &mut HashMap<String, String>) {
                         continue;
                     };
 
-                    if let Type::Path(TypePath { path, .. }) = &field.ty {
+                    if let Type::Path(TypePath { path, .. }) = unboxed(&field.ty) {
                         let segments = &path.segments;
                         let Some(wrapper_segment) = segments.last() else {
                             continue;
                         };
 
-                        // Look for Account<'info, X> or Signer<'info> or Program<'info, X>
+                        // A field of another `Accounts` struct type is a composite group
+                        if accounts_structs.contains(&wrapper_segment.ident.to_string()) {
+                            map.insert(field_name.to_string(), wrapper_segment.ident.to_string());
+                            continue;
+                        }
+
+                        // Look for Account<'info, X> or Signer<'info> or Program<'info, X>, and their
+                        // `token_interface` counterparts InterfaceAccount<'info, X> and Interface<'info, X>
                         if let PathArguments::AngleBracketed(args) = &wrapper_segment.arguments {
                             if wrapper_segment.ident == "Account"
                                 || wrapper_segment.ident == "Program"
+                                || wrapper_segment.ident == "InterfaceAccount"
+                                || wrapper_segment.ident == "Interface"
                             {
-                                if args.args.len() == 2 {
-                                    if let syn::GenericArgument::Type(Type::Path(inner_ty)) =
+                                if args.args.len() == 2
+                                    && let syn::GenericArgument::Type(Type::Path(inner_ty)) =
                                         &args.args[1]
-                                    {
-                                        if let Some(struct_name) = inner_ty.path.segments.last() {
-                                            map.insert(
-                                                field_name.to_string(),
-                                                struct_name.ident.to_string(),
-                                            );
-                                        }
-                                    }
+                                    && let Some(struct_name) = inner_ty.path.segments.last()
+                                {
+                                    // Generic account types keep their arguments, e.g. `Wrapper<u64,4>`
+                                    let generic_args = struct_name.arguments.to_token_stream().to_string().replace(' ', "");
+                                    let type_name = format!("{}{}", struct_name.ident, generic_args);
+                                    // Programs keep their wrapper, e.g. `Program<System>`, they have no account data
+                                    let type_name = if wrapper_segment.ident == "Program" || wrapper_segment.ident == "Interface" {
+                                        format!("{}<{}>", wrapper_segment.ident, type_name)
+                                    } else {
+                                        type_name
+                                    };
+                                    map.insert(field_name.to_string(), type_name);
                                 }
                             } else if wrapper_segment.ident == "Signer" {
                                 // Signers have only 1 type param: 'info
@@ -1,36 +1,41 @@
 use std::path::{Path, PathBuf};
 
-use tempfile::{tempdir, TempDir};
-
+use crate::cache::cache_dir;
 use crate::utils::{binary_name_from_package, cli_error, inject_workspace_member};
 
 
-pub fn prepare_output_path(user_out: &Option<String>) -> Result<(PathBuf, bool, Option<TempDir>), Box<dyn std::error::Error>> {
+// The wrapper is ephemeral (not part of the user's workspace) when no output path is given, it then lives in the cache dir
+pub fn prepare_output_path(user_out: &Option<String>, package: &str) -> Result<(PathBuf, bool), Box<dyn std::error::Error>> {
     if let Some(user_path) = user_out {
-        Ok((PathBuf::from(user_path), false, None))
+        Ok((PathBuf::from(user_path), false))
     } else {
-        let temp = tempdir()?;
-        Ok((temp.path().to_path_buf(), true, Some(temp)))
+        Ok((cache_dir(package)?, true))
     }
 }
 
-// If the output path is not tempDir that means i need to add inside root cargo the name of this crate which is going to be created by this CLI tool
+// If the output path is not the cache dir that means i need to add inside root cargo the name of this crate which is going to be created by this CLI tool
 pub fn maybe_inject_workspace(out_path: &Path, is_ephemeral: bool) {
-    if !is_ephemeral {
-        if let Ok(root_dir) = std::env::current_dir() {
-            let root_cargo = root_dir.join("Cargo.toml");
-            if let Err(e) = inject_workspace_member(&root_cargo, out_path.to_str().unwrap()) {
-                cli_error(e);
-            }
+    if !is_ephemeral
+        && let Ok(root_dir) = std::env::current_dir()
+    {
+        let root_cargo = root_dir.join("Cargo.toml");
+        if let Err(e) = inject_workspace_member(&root_cargo, out_path.to_str().unwrap()) {
+            cli_error(e);
         }
     }
 }
 
+// Cargo target dirs of the wrappers. The cached wrapper builds apart from the ones generated into an output dir: they
+// share the binary name, and a build of one must not be taken for the other's cached binary
+const OUT_TARGET_DIR: &str = "target/debuggen";
+const CACHE_TARGET_DIR: &str = "target/debuggen/cache";
+
 // This should build the tempDir into a specified path to maintain the compiled data and then just move the exe binary used for debbugging to provide easy access
 pub fn build_and_extract_binary(
     package: &str,
-    out_path: &Path
-) -> Result<(), Box<dyn std::error::Error>> {
+    out_path: &Path,
+    is_ephemeral: bool,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
     let manifest_path = out_path.join("Cargo.toml");
 
     let build_status = std::process::Command::new("cargo")
@@ -39,25 +44,47 @@ pub fn build_and_extract_binary(
             "--manifest-path",
             manifest_path.to_str().unwrap(),
             "--target-dir",
-            "target/debuggen",
+            target_dir(is_ephemeral),
         ])
         .status()?;
 
     if !build_status.success() {
-        panic!("Failed to uild the debug wrapper in order to create the exe binary file.");
+        return Err("Failed to build the debug wrapper in order to create the exe binary file.".into());
     }
 
-    let binary_name = binary_name_from_package(package);
-    let built_bin_path = PathBuf::from("target/debuggen/debug").join(&binary_name);
+    extract_binary(package, is_ephemeral)
+}
+
+// Copies the built binary to `target/debug/<package>` to provide easy access
+pub fn extract_binary(package: &str, is_ephemeral: bool) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let built_bin_path = built_binary_path(package, is_ephemeral);
     let bin_out_path = std::env::current_dir()?.join("target/debug").join(package);
 
     std::fs::copy(&built_bin_path, &bin_out_path)?;
     println!("\n[INFO] Debug binary successfully written to:\n -> {}\n::BIN_OUT::{}\n", bin_out_path.display(), bin_out_path.display());
 
-    let run_status = std::process::Command::new(&bin_out_path).status()?;
+    Ok(bin_out_path)
+}
+
+// Where cargo leaves the cached wrapper binary, it outlives the temp dir the wrapper was generated in
+pub fn cached_binary_path(package: &str) -> PathBuf {
+    built_binary_path(package, true)
+}
+
+fn built_binary_path(package: &str, is_ephemeral: bool) -> PathBuf {
+    PathBuf::from(target_dir(is_ephemeral)).join("debug").join(binary_name_from_package(package))
+}
+
+fn target_dir(is_ephemeral: bool) -> &'static str {
+    if is_ephemeral { CACHE_TARGET_DIR } else { OUT_TARGET_DIR }
+}
+
+// Runs the extracted debug binary directly, without a debugger attached
+pub fn run_binary(bin_path: &Path, bin_args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let run_status = std::process::Command::new(bin_path).args(bin_args).status()?;
 
     if !run_status.success() {
-        panic!("Execution of the deug wrapper binary failed.");
+        return Err(format!("debug wrapper exited with {run_status}").into());
     }
 
     Ok(())
@@ -0,0 +1,580 @@
+/*
+    This will be generated as a static codec that turns JSON values into the Borsh encoding of the instruction arguments.
+    The IDL is embedded into the binary so arguments can be supplied at runtime without regenerating the wrapper.
+*/
+
+use anchor_lang::prelude::Pubkey;
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+use crate::int256::le_bytes_256;
+
+pub const IDL_JSON: &str = include_str!("idl.json");
+
+pub fn idl() -> Value {
+    serde_json::from_str(IDL_JSON).expect("The embedded IDL is not valid JSON")
+}
+
+pub fn instruction<'a>(idl: &'a Value, name: &str) -> Option<&'a Value> {
+    idl["instructions"]
+        .as_array()?
+        .iter()
+        .find(|ix| ix["name"] == name)
+}
+
+/// `(name, type)` of every argument of the instruction, in handler order
+pub fn instruction_args(ix: &Value) -> Vec<(String, Value)> {
+    ix["args"]
+        .as_array()
+        .map(|args| {
+            args.iter()
+                .map(|arg| (arg["name"].as_str().unwrap_or_default().to_string(), arg["type"].clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Paths of every leaf account of the instruction (`group.account` in nested account groups), the names of their fixtures
+pub fn instruction_accounts(ix: &Value) -> Vec<String> {
+    fn visit(items: &Value, scope: &str, names: &mut Vec<String>) {
+        for item in items.as_array().into_iter().flatten() {
+            let Some(name) = item["name"].as_str() else {
+                continue;
+            };
+            let path = if scope.is_empty() { name.to_string() } else { format!("{scope}.{name}") };
+            if item["accounts"].is_array() {
+                visit(&item["accounts"], &path, names);
+            } else {
+                names.push(path);
+            }
+        }
+    }
+
+    let mut names = vec![];
+    visit(&ix["accounts"], "", &mut names);
+    names
+}
+
+pub fn find_type<'a>(idl: &'a Value, name: &str) -> Result<&'a Value, String> {
+    idl["types"]
+        .as_array()
+        .and_then(|types| types.iter().find(|t| t["name"] == name))
+        .ok_or(format!("type `{name}` not found in the IDL"))
+}
+
+/// The type definition with the generic arguments of the use site (`defined`) in place of its `{"generic": name}` parameters
+pub fn instantiate(type_def: &Value, defined: &Value) -> Value {
+    fn substitute(value: &Value, params: &serde_json::Map<String, Value>) -> Value {
+        match value {
+            Value::Object(o) => {
+                if let Some(param) = o.get("generic").and_then(Value::as_str).and_then(|name| params.get(name)) {
+                    return param.clone();
+                }
+                // A const parameter forwarded to a nested generic type
+                if o.get("kind").and_then(Value::as_str) == Some("const") {
+                    if let Some(param) = o.get("value").and_then(Value::as_str).and_then(|name| params.get(name)) {
+                        return json!({ "kind": "const", "value": param.to_string() });
+                    }
+                }
+                Value::Object(o.iter().map(|(k, v)| (k.clone(), substitute(v, params))).collect())
+            }
+            Value::Array(items) => Value::Array(items.iter().map(|item| substitute(item, params)).collect()),
+            other => other.clone(),
+        }
+    }
+
+    let (Some(params), Some(args)) = (type_def["generics"].as_array(), defined["generics"].as_array()) else {
+        return type_def.clone();
+    };
+
+    // Type parameters become their type, const parameters their value (array lengths)
+    let params = params
+        .iter()
+        .zip(args)
+        .map(|(param, arg)| {
+            let value = match arg["kind"].as_str() {
+                Some("const") => {
+                    let text = arg["value"].as_str().unwrap_or_default();
+                    text.parse::<u64>().map(Value::from).unwrap_or(json!(text))
+                }
+                _ => arg["type"].clone(),
+            };
+            (param["name"].as_str().unwrap_or_default().to_string(), value)
+        })
+        .collect();
+
+    json!({ "name": type_def["name"], "type": substitute(&type_def["type"], &params) })
+}
+
+/// Human readable form of an IDL type, e.g. `Vec<Option<u64>>`
+pub fn type_name(ty: &Value) -> String {
+    match ty {
+        Value::String(s) => s.clone(),
+        Value::Object(o) => {
+            if let Some(inner) = o.get("option") {
+                format!("Option<{}>", type_name(inner))
+            } else if let Some(inner) = o.get("vec") {
+                format!("Vec<{}>", type_name(inner))
+            } else if let Some(array) = o.get("array") {
+                format!("[{}; {}]", type_name(&array[0]), array[1])
+            } else if let Some(defined) = o.get("defined") {
+                defined["name"].as_str().or(defined.as_str()).unwrap_or("?").to_string()
+            } else if let Some(generic) = o.get("generic") {
+                generic.as_str().unwrap_or("?").to_string()
+            } else {
+                ty.to_string()
+            }
+        }
+        _ => ty.to_string(),
+    }
+}
+
+/// The value used when none is given, mirrors the dummy values of the generated `call_<ix>` functions
+pub fn default_value(idl: &Value, ty: &Value) -> Result<Value, String> {
+    Ok(match ty {
+        Value::String(s) => match s.as_str() {
+            "bool" => json!(false),
+            "string" => json!("test"),
+            "pubkey" => json!(Pubkey::new_unique().to_string()),
+            "bytes" => json!(b"test".to_vec()),
+            _ => json!(0),
+        },
+        Value::Object(o) => {
+            if let Some(inner) = o.get("option") {
+                default_value(idl, inner)?
+            } else if let Some(inner) = o.get("vec") {
+                json!([default_value(idl, inner)?])
+            } else if let Some(array) = o.get("array") {
+                let len = array[1].as_u64().ok_or("generic array lengths are not supported")?;
+                Value::Array(
+                    (0..len)
+                        .map(|_| default_value(idl, &array[0]))
+                        .collect::<Result<_, _>>()?,
+                )
+            } else if let Some(defined) = o.get("defined") {
+                let name = defined["name"].as_str().or(defined.as_str()).unwrap_or_default();
+                default_defined(idl, &instantiate(find_type(idl, name)?, defined))?
+            } else {
+                return Err(format!("no default value for type {}", type_name(ty)));
+            }
+        }
+        _ => return Err(format!("malformed IDL type {ty}")),
+    })
+}
+
+fn default_fields(idl: &Value, fields: &Value) -> Result<Value, String> {
+    let Some(fields) = fields.as_array() else {
+        return Ok(Value::Null);
+    };
+
+    if fields.iter().all(|f| f.get("name").is_some()) {
+        let mut object = serde_json::Map::new();
+        for field in fields {
+            object.insert(
+                field["name"].as_str().unwrap_or_default().to_string(),
+                default_value(idl, &field["type"])?,
+            );
+        }
+        Ok(Value::Object(object))
+    } else {
+        Ok(Value::Array(
+            fields.iter().map(|ty| default_value(idl, ty)).collect::<Result<_, _>>()?,
+        ))
+    }
+}
+
+fn default_defined(idl: &Value, type_def: &Value) -> Result<Value, String> {
+    let ty = &type_def["type"];
+    match ty["kind"].as_str() {
+        Some("struct") => default_fields(idl, &ty["fields"]),
+        Some("enum") => {
+            let variant = &ty["variants"][0];
+            let name = variant["name"].as_str().ok_or("enum without variants")?;
+            if variant["fields"].is_null() {
+                Ok(json!(name))
+            } else {
+                Ok(json!({ name: default_fields(idl, &variant["fields"])? }))
+            }
+        }
+        Some("type") => default_value(idl, &ty["alias"]),
+        _ => Err(format!("unsupported type definition {}", type_def["name"])),
+    }
+}
+
+fn as_number<T: FromStr>(value: &Value, ty: &str) -> Result<T, String> {
+    let text = match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        _ => return Err(format!("expected {ty}, got {value}")),
+    };
+    text.parse().map_err(|_| format!("expected {ty}, got {value}"))
+}
+
+/// Appends the Borsh encoding of `value` interpreted as the IDL type `ty`
+pub fn encode_value(idl: &Value, ty: &Value, value: &Value, out: &mut Vec<u8>) -> Result<(), String> {
+    match ty {
+        Value::String(s) => match s.as_str() {
+            "bool" => out.push(value.as_bool().ok_or(format!("expected bool, got {value}"))? as u8),
+            "u8" => out.extend(as_number::<u8>(value, s)?.to_le_bytes()),
+            "i8" => out.extend(as_number::<i8>(value, s)?.to_le_bytes()),
+            "u16" => out.extend(as_number::<u16>(value, s)?.to_le_bytes()),
+            "i16" => out.extend(as_number::<i16>(value, s)?.to_le_bytes()),
+            "u32" => out.extend(as_number::<u32>(value, s)?.to_le_bytes()),
+            "i32" => out.extend(as_number::<i32>(value, s)?.to_le_bytes()),
+            "u64" => out.extend(as_number::<u64>(value, s)?.to_le_bytes()),
+            "i64" => out.extend(as_number::<i64>(value, s)?.to_le_bytes()),
+            "u128" => out.extend(as_number::<u128>(value, s)?.to_le_bytes()),
+            "i128" => out.extend(as_number::<i128>(value, s)?.to_le_bytes()),
+            // No native 256-bit integer, decimal values are parsed into their little endian bytes
+            "u256" | "i256" => {
+                let bytes = match value {
+                    Value::Number(n) => le_bytes_256(&n.to_string(), s == "i256"),
+                    Value::String(text) => le_bytes_256(text, s == "i256"),
+                    _ => None,
+                };
+                out.extend(bytes.ok_or(format!("expected {s}, got {value}"))?);
+            }
+            "f32" => out.extend(as_number::<f32>(value, s)?.to_le_bytes()),
+            "f64" => out.extend(as_number::<f64>(value, s)?.to_le_bytes()),
+            "string" => {
+                let text = value.as_str().ok_or(format!("expected string, got {value}"))?;
+                out.extend((text.len() as u32).to_le_bytes());
+                out.extend(text.as_bytes());
+            }
+            "pubkey" => {
+                let text = value.as_str().ok_or(format!("expected base58 pubkey, got {value}"))?;
+                let key = Pubkey::from_str(text).map_err(|e| format!("invalid pubkey `{text}`: {e}"))?;
+                out.extend(key.to_bytes());
+            }
+            "bytes" => {
+                let bytes = decode_bytes(value)?;
+                out.extend((bytes.len() as u32).to_le_bytes());
+                out.extend(bytes);
+            }
+            other => return Err(format!("unsupported type `{other}`")),
+        },
+        Value::Object(o) => {
+            if let Some(inner) = o.get("option") {
+                if value.is_null() {
+                    out.push(0);
+                } else {
+                    out.push(1);
+                    encode_value(idl, inner, value, out)?;
+                }
+            } else if let Some(inner) = o.get("vec") {
+                let items = value.as_array().ok_or(format!("expected an array, got {value}"))?;
+                out.extend((items.len() as u32).to_le_bytes());
+                for item in items {
+                    encode_value(idl, inner, item, out)?;
+                }
+            } else if let Some(array) = o.get("array") {
+                let items = value.as_array().ok_or(format!("expected an array, got {value}"))?;
+                if Some(items.len() as u64) != array[1].as_u64() {
+                    return Err(format!("expected {} items, got {}", array[1], items.len()));
+                }
+                for item in items {
+                    encode_value(idl, &array[0], item, out)?;
+                }
+            } else if let Some(defined) = o.get("defined") {
+                let name = defined["name"].as_str().or(defined.as_str()).unwrap_or_default();
+                encode_defined(idl, &instantiate(find_type(idl, name)?, defined), value, out)?;
+            } else {
+                return Err(format!("unsupported type {}", type_name(ty)));
+            }
+        }
+        _ => return Err(format!("malformed IDL type {ty}")),
+    }
+
+    Ok(())
+}
+
+// An array of numbers, a base64 string or a `0x` prefixed hex string, like the `--args` file of the generator
+fn decode_bytes(value: &Value) -> Result<Vec<u8>, String> {
+    let text = match value {
+        Value::Array(items) => return items.iter().map(|byte| as_number::<u8>(byte, "u8")).collect(),
+        Value::String(text) => text,
+        _ => return Err(format!("expected bytes (base64, 0x-prefixed hex or an array of numbers), got {value}")),
+    };
+
+    if let Some(hex) = text.strip_prefix("0x") {
+        if hex.len() % 2 != 0 {
+            return Err(format!("invalid hex bytes `{text}`"));
+        }
+        return (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2).unwrap_or_default(), 16).map_err(|_| format!("invalid hex bytes `{text}`")))
+            .collect();
+    }
+
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut bytes = vec![];
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text.trim_end_matches('=').bytes() {
+        let digit = ALPHABET.iter().position(|&a| a == c).ok_or(format!("invalid base64 bytes `{text}`"))?;
+        buffer = (buffer << 6) | digit as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Borsh encoding of the arguments of the instruction `ix`, in handler order.
+/// `values` maps argument names to JSON values, the missing ones get their default value.
+pub fn encode_args(idl: &Value, ix: &Value, values: &Value) -> Result<Vec<u8>, String> {
+    let args = instruction_args(ix);
+    let values = values.as_object().ok_or(format!("expected an object of argument values, got {values}"))?;
+    if let Some(unknown) = values.keys().find(|name| !args.iter().any(|(arg, _)| arg == *name)) {
+        return Err(format!("unknown argument `{unknown}`"));
+    }
+
+    let mut data = vec![];
+    for (arg, ty) in &args {
+        let value = match values.get(arg) {
+            Some(value) => value.clone(),
+            None => default_value(idl, ty).map_err(|e| format!("argument `{arg}`: {e}"))?,
+        };
+        encode_value(idl, ty, &value, &mut data).map_err(|e| format!("argument `{arg}`: {e}"))?;
+    }
+    Ok(data)
+}
+
+// `path` names the struct or the enum variant in errors, e.g. `Mode::Fixed`
+fn encode_fields(idl: &Value, path: &str, fields: &Value, value: &Value, out: &mut Vec<u8>) -> Result<(), String> {
+    let Some(fields) = fields.as_array() else {
+        return Ok(());
+    };
+
+    // A misspelled field would otherwise be dropped and the field it was meant for reported missing
+    if let Some(object) = value.as_object() {
+        if let Some(unknown) = object.keys().find(|key| !fields.iter().any(|f| f["name"] == key.as_str())) {
+            return Err(format!("`{path}` has no field `{unknown}`"));
+        }
+    }
+
+    for (i, field) in fields.iter().enumerate() {
+        match field.get("name").and_then(Value::as_str) {
+            Some(name) => {
+                let field_value = value.get(name).ok_or(format!("missing field `{name}`"))?;
+                encode_value(idl, &field["type"], field_value, out)
+                    .map_err(|e| format!("field `{name}`: {e}"))?;
+            }
+            None => {
+                let field_value = value.get(i).ok_or(format!("missing tuple field {i}"))?;
+                encode_value(idl, field, field_value, out).map_err(|e| format!("field {i}: {e}"))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn encode_defined(idl: &Value, type_def: &Value, value: &Value, out: &mut Vec<u8>) -> Result<(), String> {
+    let ty = &type_def["type"];
+    match ty["kind"].as_str() {
+        Some("struct") => encode_fields(idl, type_def["name"].as_str().unwrap_or_default(), &ty["fields"], value, out),
+        Some("enum") => {
+            // `"Variant"` for unit variants, `{ "Variant": fields }` otherwise
+            let (variant_name, fields_value) = match value {
+                Value::String(name) => (name.as_str(), &Value::Null),
+                Value::Object(o) if o.len() == 1 => {
+                    let (name, fields) = o.iter().next().unwrap();
+                    (name.as_str(), fields)
+                }
+                _ => return Err(format!("expected an enum variant, got {value}")),
+            };
+
+            let variants = ty["variants"].as_array().ok_or("enum without variants")?;
+            let index = variants
+                .iter()
+                .position(|v| v["name"] == variant_name)
+                .ok_or(format!("unknown variant `{variant_name}`"))?;
+
+            out.push(index as u8);
+            let path = format!("{}::{variant_name}", type_def["name"].as_str().unwrap_or_default());
+            encode_fields(idl, &path, &variants[index]["fields"], fields_value, out)
+        }
+        Some("type") => encode_value(idl, &ty["alias"], value, out),
+        _ => Err(format!("unsupported type definition {}", type_def["name"])),
+    }
+}
+
+/// Parses what was typed at a prompt: JSON when it parses, otherwise the raw text as a string (pubkeys, names)
+pub fn parse_input(input: &str) -> Value {
+    // Integers beyond 64 bits would be rounded by the JSON number type, the encoder parses them from the text
+    let digits = input.strip_prefix('-').unwrap_or(input);
+    if !digits.is_empty() && digits.bytes().all(|d| d.is_ascii_digit()) {
+        return Value::String(input.to_string());
+    }
+    serde_json::from_str(input).unwrap_or_else(|_| Value::String(input.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::{borsh, AnchorDeserialize, AnchorSerialize};
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Debug, PartialEq)]
+    struct Config {
+        fee_bps: u16,
+        mode: Mode,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Debug, PartialEq)]
+    enum Mode {
+        Off,
+        Fixed { rate: u8 },
+        Pair(u8, u16),
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Debug, PartialEq)]
+    struct Args {
+        amount: u64,
+        delta: i128,
+        config: Config,
+        memo: Vec<u8>,
+        owner: Pubkey,
+        limit: Option<u32>,
+        steps: Vec<i16>,
+        tag: [u8; 3],
+        pair: Config,
+    }
+
+    fn idl() -> Value {
+        json!({
+            "instructions": [{
+                "name": "configure",
+                "args": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "delta", "type": "i128" },
+                    { "name": "config", "type": { "defined": { "name": "Config" } } },
+                    { "name": "memo", "type": "bytes" },
+                    { "name": "owner", "type": "pubkey" },
+                    { "name": "limit", "type": { "option": "u32" } },
+                    { "name": "steps", "type": { "vec": "i16" } },
+                    { "name": "tag", "type": { "array": ["u8", 3] } },
+                    { "name": "pair", "type": { "defined": { "name": "Config" } } }
+                ]
+            }],
+            "types": [
+                {
+                    "name": "Config",
+                    "type": { "kind": "struct", "fields": [
+                        { "name": "fee_bps", "type": "u16" },
+                        { "name": "mode", "type": { "defined": { "name": "Mode" } } }
+                    ] }
+                },
+                {
+                    "name": "Mode",
+                    "type": { "kind": "enum", "variants": [
+                        { "name": "Off" },
+                        { "name": "Fixed", "fields": [{ "name": "rate", "type": "u8" }] },
+                        { "name": "Pair", "fields": ["u8", "u16"] }
+                    ] }
+                }
+            ]
+        })
+    }
+
+    #[test]
+    fn encodes_the_borsh_layout_of_the_arguments() {
+        let idl = idl();
+        let ix = instruction(&idl, "configure").unwrap();
+        let owner = Pubkey::new_from_array([3; 32]);
+        let values = json!({
+            "amount": 1000,
+            "delta": "-170141183460469231731687303715884105728",
+            "config": { "fee_bps": 25, "mode": { "Fixed": { "rate": 3 } } },
+            "memo": "0xdeadbeef",
+            "owner": owner.to_string(),
+            "limit": null,
+            "steps": [-1, 2],
+            "tag": [1, 2, 3],
+            "pair": { "fee_bps": 0, "mode": { "Pair": [4, 500] } }
+        });
+
+        let data = encode_args(&idl, ix, &values).unwrap();
+        let expected = Args {
+            amount: 1000,
+            delta: i128::MIN,
+            config: Config { fee_bps: 25, mode: Mode::Fixed { rate: 3 } },
+            memo: vec![0xde, 0xad, 0xbe, 0xef],
+            owner,
+            limit: None,
+            steps: vec![-1, 2],
+            tag: [1, 2, 3],
+            pair: Config { fee_bps: 0, mode: Mode::Pair(4, 500) },
+        };
+        assert_eq!(data, expected.try_to_vec().unwrap());
+        assert_eq!(Args::try_from_slice(&data).unwrap(), expected);
+    }
+
+    #[test]
+    fn missing_arguments_get_their_default_value() {
+        let idl = idl();
+        let ix = instruction(&idl, "configure").unwrap();
+
+        let data = encode_args(&idl, ix, &json!({ "amount": 5 })).unwrap();
+        let args = Args::try_from_slice(&data).unwrap();
+        assert_eq!(args.amount, 5);
+        assert_eq!(args.config, Config { fee_bps: 0, mode: Mode::Off });
+        assert_eq!(args.memo, b"test");
+        assert_eq!(args.limit, Some(0));
+        assert_eq!(args.steps, vec![0]);
+    }
+
+    #[test]
+    fn rejects_values_that_do_not_match_the_type() {
+        let idl = idl();
+        let ix = instruction(&idl, "configure").unwrap();
+
+        let error = encode_args(&idl, ix, &json!({ "amount": -1 })).unwrap_err();
+        assert!(error.starts_with("argument `amount`: expected u64"), "{error}");
+        let error = encode_args(&idl, ix, &json!({ "config": { "fee_bps": 1, "mode": "Auto" } })).unwrap_err();
+        assert!(error.contains("unknown variant `Auto`"), "{error}");
+        let error = encode_args(&idl, ix, &json!({ "amout": 1 })).unwrap_err();
+        assert_eq!(error, "unknown argument `amout`");
+        let error = encode_args(&idl, ix, &json!({ "config": { "fee_bps": 1, "fee": 2, "mode": "Off" } })).unwrap_err();
+        assert!(error.ends_with("`Config` has no field `fee`"), "{error}");
+        let error = encode_args(&idl, ix, &json!({ "config": { "fee_bps": 1, "mode": { "Fixed": { "rat": 3 } } } })).unwrap_err();
+        assert!(error.ends_with("`Mode::Fixed` has no field `rat`"), "{error}");
+    }
+
+    #[test]
+    fn decodes_base64_hex_and_number_arrays() {
+        assert_eq!(decode_bytes(&json!("dGVzdA==")).unwrap(), b"test");
+        assert_eq!(decode_bytes(&json!("dGVzdA")).unwrap(), b"test");
+        assert_eq!(decode_bytes(&json!("AAEC/w==")).unwrap(), [0, 1, 2, 255]);
+        assert_eq!(decode_bytes(&json!("0xdeadBEEF")).unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(decode_bytes(&json!("0x")).unwrap(), Vec::<u8>::new());
+        assert_eq!(decode_bytes(&json!([1, 2, 255])).unwrap(), [1, 2, 255]);
+
+        assert!(decode_bytes(&json!("0xabc")).is_err());
+        assert!(decode_bytes(&json!("0xzz")).is_err());
+        assert!(decode_bytes(&json!("not*base64")).is_err());
+        assert!(decode_bytes(&json!([256])).is_err());
+    }
+
+    #[test]
+    fn integers_of_256_bits_encode_to_their_little_endian_bytes() {
+        let i256_min = "-57896044618658097711785492504343953926634992332820282019728792003956564819968";
+
+        let mut data = vec![];
+        encode_value(&json!({}), &json!("i256"), &json!(i256_min), &mut data).unwrap();
+        encode_value(&json!({}), &json!("u256"), &json!(7), &mut data).unwrap();
+        assert_eq!(&data[..32], &le_bytes_256(i256_min, true).unwrap());
+        assert_eq!(data[32], 7);
+        assert!(encode_value(&json!({}), &json!("u256"), &json!("-7"), &mut data).is_err());
+    }
+
+    #[test]
+    fn large_integers_typed_at_a_prompt_stay_exact() {
+        assert_eq!(parse_input("340282366920938463463374607431768211455"), json!("340282366920938463463374607431768211455"));
+        assert_eq!(parse_input("-57896044618658097711785492504343953926634992332820282019728792003956564819968"), json!("-57896044618658097711785492504343953926634992332820282019728792003956564819968"));
+        assert_eq!(parse_input("-"), json!("-"));
+        assert_eq!(parse_input("{\"Fixed\": {\"rate\": 3}}"), json!({ "Fixed": { "rate": 3 } }));
+        assert_eq!(parse_input("Off"), json!("Off"));
+    }
+}
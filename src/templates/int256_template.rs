@@ -0,0 +1,110 @@
+/*
+    This will be generated as a static module converting 256-bit integers between their decimal text and their little
+    endian Borsh bytes, Rust has no native type for them. The generator compiles it in too for the `--args` values.
+*/
+
+/// Little endian bytes of a decimal 256-bit integer (two's complement when negative), `None` when it is out of range
+pub fn le_bytes_256(text: &str, signed: bool) -> Option<[u8; 32]> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) if signed => (true, digits),
+        _ => (false, text),
+    };
+    if digits.is_empty() || !digits.bytes().all(|d| d.is_ascii_digit()) {
+        return None;
+    }
+
+    // 64-bit limbs, the least significant first
+    let mut limbs = [0u64; 4];
+    for digit in digits.bytes() {
+        let mut carry = (digit - b'0') as u128;
+        for limb in &mut limbs {
+            let product = *limb as u128 * 10 + carry;
+            *limb = product as u64;
+            carry = product >> 64;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+
+    // The sign bit is only set by the magnitude of MIN
+    if signed && limbs[3] >> 63 == 1 && !(negative && limbs == [0, 0, 0, 1 << 63]) {
+        return None;
+    }
+    if negative {
+        negate(&mut limbs);
+    }
+
+    let mut bytes = [0u8; 32];
+    for (chunk, limb) in bytes.chunks_mut(8).zip(limbs) {
+        chunk.copy_from_slice(&limb.to_le_bytes());
+    }
+    Some(bytes)
+}
+
+/// Decimal text of a little endian 256-bit integer, read as two's complement when `signed`
+pub fn decimal_256(bytes: &[u8; 32], signed: bool) -> String {
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+        *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    let negative = signed && limbs[3] >> 63 == 1;
+    if negative {
+        negate(&mut limbs);
+    }
+
+    let mut digits = vec![];
+    loop {
+        let mut remainder = 0u128;
+        for limb in limbs.iter_mut().rev() {
+            let dividend = remainder << 64 | *limb as u128;
+            *limb = (dividend / 10) as u64;
+            remainder = dividend % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+        if limbs == [0; 4] {
+            break;
+        }
+    }
+    if negative {
+        digits.push(b'-');
+    }
+    digits.iter().rev().map(|&d| d as char).collect()
+}
+
+// Two's complement negation
+fn negate(limbs: &mut [u64; 4]) {
+    let mut carry = true;
+    for limb in limbs {
+        (*limb, carry) = (!*limb).overflowing_add(carry as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers_of_256_bits_round_trip_through_their_bytes() {
+        let u256_max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        let i256_min = "-57896044618658097711785492504343953926634992332820282019728792003956564819968";
+        let i256_max = "57896044618658097711785492504343953926634992332820282019728792003956564819967";
+
+        assert_eq!(le_bytes_256(u256_max, false), Some([0xff; 32]));
+        assert_eq!(le_bytes_256("-1", true), Some([0xff; 32]));
+        assert_eq!(le_bytes_256("-0", true), Some([0; 32]));
+        for (text, signed) in [("0", false), ("1", false), (u256_max, false), ("-1", true), (i256_min, true), (i256_max, true)] {
+            assert_eq!(decimal_256(&le_bytes_256(text, signed).unwrap(), signed), text);
+        }
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        assert_eq!(decimal_256(&one, true), "1");
+
+        // One past each end, and negative unsigned values
+        assert_eq!(le_bytes_256("115792089237316195423570985008687907853269984665640564039457584007913129639936", false), None);
+        assert_eq!(le_bytes_256("57896044618658097711785492504343953926634992332820282019728792003956564819968", true), None);
+        assert_eq!(le_bytes_256("-57896044618658097711785492504343953926634992332820282019728792003956564819969", true), None);
+        assert_eq!(le_bytes_256("-1", false), None);
+        assert_eq!(le_bytes_256("", false), None);
+    }
+}
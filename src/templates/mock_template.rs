@@ -1,6 +1,9 @@
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
 use anchor_lang::Discriminator;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 /*
     This will be generated as a static mock code used to generate the common contexts
@@ -35,22 +38,29 @@ pub fn mock_signer_account(label: &str) -> AccountInfo<'static> {
 }
 
 /// Mocks a PDA AccountInfo with some dummy data
+/// `size` is the length of the zeroed account (discriminator included), at least 64 bytes are allocated
+/// plus some room for strings and vectors to grow, like the `space` a real account is created with
 pub fn mock_pda_account<T: Discriminator>(seeds: &[&[u8]], program_id: &Pubkey, size: usize) -> AccountInfo<'static> {
     let (pda, _bump) = Pubkey::find_program_address(seeds, program_id);
-    let pda = Box::leak(Box::new(pda));
+    mock_data_account::<T>(pda, program_id, size)
+}
+
+/// Mocks an account of `program_id` at a fixed key (`#[account(address = ...)]`), laid out like `mock_pda_account`
+pub fn mock_data_account<T: Discriminator>(key: Pubkey, program_id: &Pubkey, size: usize) -> AccountInfo<'static> {
+    let key = Box::leak(Box::new(key));
 
     let lamports = Box::leak(Box::new(1_000_000u64));
 
     // Anchor expects the first 8 bytes of the account data to be a unique discriminator for the account type
-    let mut data = [0u8; 64];
+    let mut data = vec![0u8; size.max(64) + 1024];
     let discriminator = T::DISCRIMINATOR;
-    data[..8].copy_from_slice(&discriminator);
-    let data = Box::leak(Box::new(data)); 
+    data[..8].copy_from_slice(discriminator);
+    let data = Box::leak(data.into_boxed_slice());
 
     let owner = Box::leak(Box::new(*program_id));
 
     AccountInfo::new(
-        pda,
+        key,
         false,
         true,
         lamports,
@@ -61,12 +71,25 @@ pub fn mock_pda_account<T: Discriminator>(seeds: &[&[u8]], program_id: &Pubkey,
     )
 }
 
-/// Returns a dummy system program AccountInfo
-pub fn mock_system_program() -> AccountInfo<'static> {
-    let key = Box::leak(Box::new(anchor_lang::system_program::ID));
+const BPF_LOADER_UPGRADEABLE_ID: Pubkey = pubkey!("BPFLoaderUpgradeab1e11111111111111111111111");
+const SYSVAR_PROGRAM_ID: Pubkey = pubkey!("Sysvar1111111111111111111111111111111111111");
+
+/// Mocks an executable program account owned by the `loader` that deployed it
+pub fn mock_program_account(key: Pubkey, loader: Pubkey) -> AccountInfo<'static> {
+    // Programs of the upgradeable loader point at their program data account, which `Program::programdata_address` reads
+    let data = if loader == BPF_LOADER_UPGRADEABLE_ID {
+        let (programdata, _bump) = Pubkey::find_program_address(&[key.as_ref()], &loader);
+        let mut data = 2u32.to_le_bytes().to_vec(); // `UpgradeableLoaderState::Program`
+        data.extend_from_slice(programdata.as_ref());
+        data
+    } else {
+        vec![]
+    };
+
+    let key = Box::leak(Box::new(key));
     let lamports = Box::leak(Box::new(1_000_000u64));
-    let data = Box::leak(Box::new([0u8; 10]));
-    let owner = Box::leak(Box::new(PROGRAM_ID));
+    let data = Box::leak(data.into_boxed_slice());
+    let owner = Box::leak(Box::new(loader));
 
     AccountInfo::new(
         key,
@@ -78,4 +101,442 @@ pub fn mock_system_program() -> AccountInfo<'static> {
         true, // should be executable because it is a program
         0,
     )
-}
\ No newline at end of file
+}
+
+/// Mocks a sysvar account holding `value` (e.g. `Clock { slot: 5, ..Clock::default() }`), owned by the sysvar program
+pub fn mock_sysvar_account<T: SolanaSysvar>(key: Pubkey, value: T) -> AccountInfo<'static> {
+    // `Sysvar<'info, T>` deserializes the data, so it has to be sized and laid out like the real one
+    let mut info = sysvar_account(key, vec![0u8; T::size_of()]);
+    SolanaSysvar::to_account_info(&value, &mut info).expect("sysvar data does not fit its account");
+    info
+}
+
+/// Mocks the instructions sysvar of a transaction made of `instructions`, `current` being the one executing, laid out
+/// like the runtime does so `load_instruction_at_checked` and `load_current_index_checked` read it
+pub fn mock_instructions_sysvar(key: Pubkey, instructions: &[Instruction], current: u16) -> AccountInfo<'static> {
+    // u16 count, the u16 offset of every instruction, the instructions and the u16 index of the current one
+    let mut data = (instructions.len() as u16).to_le_bytes().to_vec();
+    data.resize(2 + 2 * instructions.len(), 0);
+    for (i, ix) in instructions.iter().enumerate() {
+        let offset = data.len() as u16;
+        data[2 + 2 * i..4 + 2 * i].copy_from_slice(&offset.to_le_bytes());
+
+        data.extend_from_slice(&(ix.accounts.len() as u16).to_le_bytes());
+        for meta in &ix.accounts {
+            data.push(meta.is_signer as u8 | (meta.is_writable as u8) << 1);
+            data.extend_from_slice(meta.pubkey.as_ref());
+        }
+        data.extend_from_slice(ix.program_id.as_ref());
+        data.extend_from_slice(&(ix.data.len() as u16).to_le_bytes());
+        data.extend_from_slice(&ix.data);
+    }
+    data.extend_from_slice(&current.to_le_bytes());
+
+    sysvar_account(key, data)
+}
+
+/// Mocks a sysvar account with zeroed data, for sysvars without a layout in `anchor_lang::prelude` (e.g. `EpochRewards`)
+pub fn mock_raw_sysvar_account(key: Pubkey) -> AccountInfo<'static> {
+    sysvar_account(key, vec![0u8; 1024])
+}
+
+fn sysvar_account(key: Pubkey, data: Vec<u8>) -> AccountInfo<'static> {
+    let key = Box::leak(Box::new(key));
+    let lamports = Box::leak(Box::new(1_000_000u64));
+    let data = Box::leak(data.into_boxed_slice());
+    let owner = Box::leak(Box::new(SYSVAR_PROGRAM_ID));
+
+    AccountInfo::new(
+        key,
+        false,
+        false,
+        lamports,
+        &mut data[..],
+        owner,
+        false,
+        0,
+    )
+}
+
+/*
+    SPL Token state, packed like `spl_token::state::{Mint, Account}` so `anchor_spl`'s `Mint` and `TokenAccount` unpack it.
+    Optional keys are a `COption<Pubkey>`: a 4 byte tag (0 none, 1 some) and the 32 bytes of the key.
+*/
+
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Address of the associated token account of `wallet` for `mint`
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+fn pack_optional_key(data: &mut Vec<u8>, key: Option<Pubkey>) {
+    match key {
+        Some(key) => {
+            data.extend_from_slice(&1u32.to_le_bytes());
+            data.extend_from_slice(key.as_ref());
+        }
+        None => data.extend_from_slice(&[0u8; 36]),
+    }
+}
+
+/// Mocks an initialized mint owned by `token_program`, without freeze authority (82 bytes, more with `extensions`)
+pub fn mock_mint(
+    key: Pubkey,
+    token_program: Pubkey,
+    mint_authority: Option<Pubkey>,
+    decimals: u8,
+    supply: u64,
+    extensions: &[TokenExtension],
+) -> AccountInfo<'static> {
+    let mut data = Vec::with_capacity(82);
+    pack_optional_key(&mut data, mint_authority);
+    data.extend_from_slice(&supply.to_le_bytes());
+    data.push(decimals);
+    data.push(1); // is_initialized
+    pack_optional_key(&mut data, None); // freeze_authority
+
+    pack_extensions(&mut data, 1, extensions);
+    token_program_account(key, token_program, data)
+}
+
+/// Mocks an initialized token account of `mint` owned by `token_program`, `delegate` may spend the whole `amount`
+/// (165 bytes, more with `extensions`)
+pub fn mock_token_account(
+    key: Pubkey,
+    token_program: Pubkey,
+    mint: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+    delegate: Option<Pubkey>,
+    extensions: &[TokenExtension],
+) -> AccountInfo<'static> {
+    let mut data = Vec::with_capacity(165);
+    data.extend_from_slice(mint.as_ref());
+    data.extend_from_slice(owner.as_ref());
+    data.extend_from_slice(&amount.to_le_bytes());
+    pack_optional_key(&mut data, delegate);
+    data.push(1); // state: `AccountState::Initialized`
+    data.extend_from_slice(&[0u8; 12]); // is_native: `COption<u64>` none
+    let delegated_amount = if delegate.is_some() { amount } else { 0 };
+    data.extend_from_slice(&delegated_amount.to_le_bytes());
+    pack_optional_key(&mut data, None); // close_authority
+
+    pack_extensions(&mut data, 2, extensions);
+    token_program_account(key, token_program, data)
+}
+
+/*
+    Token-2022 extensions: after the base state (a mint is padded to the 165 bytes of a token account) comes the
+    account type (1 mint, 2 token account), then every extension as its `ExtensionType` (u16), length (u16) and value.
+    Optional keys of extensions are an `OptionalNonZeroPubkey`: 32 zero bytes for none.
+*/
+
+pub enum TokenExtension {
+    TransferFeeConfig {
+        authority: Option<Pubkey>,
+        withdraw_withheld_authority: Option<Pubkey>,
+        withheld_amount: u64,
+        maximum_fee: u64,
+        basis_points: u16,
+    },
+    TransferFeeAmount { withheld_amount: u64 },
+    MintCloseAuthority { authority: Option<Pubkey> },
+    DefaultAccountState { frozen: bool },
+    ImmutableOwner,
+    MemoTransfer { require_incoming_transfer_memos: bool },
+    NonTransferable,
+    CpiGuard { lock_cpi: bool },
+    PermanentDelegate { delegate: Option<Pubkey> },
+    TransferHook { authority: Option<Pubkey>, program_id: Option<Pubkey> },
+    MetadataPointer { authority: Option<Pubkey>, metadata_address: Option<Pubkey> },
+    TokenMetadata {
+        update_authority: Option<Pubkey>,
+        mint: Pubkey,
+        name: String,
+        symbol: String,
+        uri: String,
+        additional_metadata: Vec<(String, String)>,
+    },
+}
+
+impl TokenExtension {
+    // `ExtensionType` and value of the TLV entry
+    fn pack(&self) -> (u16, Vec<u8>) {
+        let key = |key: &Option<Pubkey>| key.unwrap_or_default().to_bytes().to_vec();
+        let string = |value: &mut Vec<u8>, text: &str| {
+            value.extend_from_slice(&(text.len() as u32).to_le_bytes());
+            value.extend_from_slice(text.as_bytes());
+        };
+
+        match self {
+            TokenExtension::TransferFeeConfig { authority, withdraw_withheld_authority, withheld_amount, maximum_fee, basis_points } => {
+                let mut value = key(authority);
+                value.extend(key(withdraw_withheld_authority));
+                value.extend_from_slice(&withheld_amount.to_le_bytes());
+                // The same fee for the older and the newer epoch
+                for _ in 0..2 {
+                    value.extend_from_slice(&0u64.to_le_bytes());
+                    value.extend_from_slice(&maximum_fee.to_le_bytes());
+                    value.extend_from_slice(&basis_points.to_le_bytes());
+                }
+                (1, value)
+            }
+            TokenExtension::TransferFeeAmount { withheld_amount } => (2, withheld_amount.to_le_bytes().to_vec()),
+            TokenExtension::MintCloseAuthority { authority } => (3, key(authority)),
+            TokenExtension::DefaultAccountState { frozen } => (6, vec![if *frozen { 2 } else { 1 }]),
+            TokenExtension::ImmutableOwner => (7, vec![]),
+            TokenExtension::MemoTransfer { require_incoming_transfer_memos } => (8, vec![*require_incoming_transfer_memos as u8]),
+            TokenExtension::NonTransferable => (9, vec![]),
+            TokenExtension::CpiGuard { lock_cpi } => (11, vec![*lock_cpi as u8]),
+            TokenExtension::PermanentDelegate { delegate } => (12, key(delegate)),
+            TokenExtension::TransferHook { authority, program_id } => (14, [key(authority), key(program_id)].concat()),
+            TokenExtension::MetadataPointer { authority, metadata_address } => (18, [key(authority), key(metadata_address)].concat()),
+            TokenExtension::TokenMetadata { update_authority, mint, name, symbol, uri, additional_metadata } => {
+                let mut value = key(update_authority);
+                value.extend_from_slice(mint.as_ref());
+                string(&mut value, name);
+                string(&mut value, symbol);
+                string(&mut value, uri);
+                value.extend_from_slice(&(additional_metadata.len() as u32).to_le_bytes());
+                for (field, field_value) in additional_metadata {
+                    string(&mut value, field);
+                    string(&mut value, field_value);
+                }
+                (19, value)
+            }
+        }
+    }
+}
+
+fn pack_extensions(data: &mut Vec<u8>, account_type: u8, extensions: &[TokenExtension]) {
+    if extensions.is_empty() {
+        return;
+    }
+
+    data.resize(165, 0);
+    data.push(account_type);
+    for extension in extensions {
+        let (extension_type, value) = extension.pack();
+        data.extend_from_slice(&extension_type.to_le_bytes());
+        data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        data.extend_from_slice(&value);
+    }
+}
+
+fn token_program_account(key: Pubkey, token_program: Pubkey, data: Vec<u8>) -> AccountInfo<'static> {
+    let key = Box::leak(Box::new(key));
+    let lamports = Box::leak(Box::new(1_000_000u64));
+    let data = Box::leak(data.into_boxed_slice());
+    let owner = Box::leak(Box::new(token_program));
+
+    AccountInfo::new(
+        key,
+        false,
+        true,
+        lamports,
+        &mut data[..],
+        owner,
+        false,
+        0,
+    )
+}
+
+/// Builds a field of an `Accounts` struct from its mock with the checks of the field type's `try_accounts`
+/// (owner and discriminator, signer, program id and executable flag, sysvar id, ...)
+pub fn account_field<T: Accounts<'static, ()>>(name: &str, info: &'static AccountInfo<'static>) -> T {
+    let mut infos: &'static [AccountInfo<'static>] = std::slice::from_ref(info);
+    T::try_accounts(&PROGRAM_ID, &mut infos, &[], &mut (), &mut BTreeSet::new())
+        .unwrap_or_else(|e| panic!("account `{}` does not fit its field: {:?}", name, e))
+}
+
+/*
+    Fixtures: every account of the generated `call_<ix>` functions is created through `fixture`.
+    Outside of a session a fresh mock is returned on every call, inside a session (the REPL) the same account is reused
+    so the state written by one instruction is visible to the next one.
+*/
+
+thread_local! {
+    static KEEP_STATE: Cell<bool> = const { Cell::new(false) };
+    static FIXTURES: RefCell<BTreeMap<String, &'static AccountInfo<'static>>> = const { RefCell::new(BTreeMap::new()) };
+    // account path of the instruction (`group.account` in composite groups) -> fixture name used in its place
+    static OVERRIDES: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+}
+
+pub fn fixture(name: &str, create: impl FnOnce() -> AccountInfo<'static>) -> &'static AccountInfo<'static> {
+    let fixture_name = OVERRIDES.with(|o| o.borrow().get(name).cloned()).unwrap_or(name.to_string());
+
+    if KEEP_STATE.with(Cell::get) {
+        if let Some(existing) = FIXTURES.with(|f| f.borrow().get(&fixture_name).copied()) {
+            return existing;
+        }
+    }
+
+    let account: &'static AccountInfo<'static> = Box::leak(Box::new(create()));
+    if KEEP_STATE.with(Cell::get) {
+        FIXTURES.with(|f| f.borrow_mut().insert(fixture_name, account));
+    }
+    account
+}
+
+pub fn keep_fixture_state(keep: bool) {
+    KEEP_STATE.with(|k| k.set(keep));
+}
+
+pub fn fixture_names() -> Vec<String> {
+    FIXTURES.with(|f| f.borrow().keys().cloned().collect())
+}
+
+pub fn set_fixture_overrides(overrides: HashMap<String, String>) {
+    OVERRIDES.with(|o| *o.borrow_mut() = overrides);
+}
+
+/// Lamports, owner and data of every fixture, used to show what an instruction changed
+pub type FixtureSnapshot = BTreeMap<String, (u64, Pubkey, Vec<u8>)>;
+
+pub fn snapshot_fixtures() -> FixtureSnapshot {
+    FIXTURES.with(|f| {
+        f.borrow()
+            .iter()
+            .map(|(name, info)| (name.clone(), (info.lamports(), *info.owner, info.data.borrow().to_vec())))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN_2022: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAbS1EZK9sWdB2B5GhDpV1xR");
+
+    fn key(byte: u8) -> Pubkey {
+        Pubkey::new_from_array([byte; 32])
+    }
+
+    // `ExtensionType`, length and value of a TLV entry
+    fn entry(extension_type: u16, value: &[u8]) -> Vec<u8> {
+        [&extension_type.to_le_bytes()[..], &(value.len() as u16).to_le_bytes(), value].concat()
+    }
+
+    #[test]
+    fn base_state_has_the_spl_token_size_without_extensions() {
+        let mint = mock_mint(key(1), TOKEN_2022, Some(key(2)), 9, 500, &[]);
+        let data = mint.data.borrow();
+        assert_eq!(data.len(), 82);
+        assert_eq!(data[..4], 1u32.to_le_bytes());
+        assert_eq!(data[4..36], key(2).to_bytes());
+        assert_eq!(data[36..44], 500u64.to_le_bytes());
+        assert_eq!(data[44..46], [9, 1]);
+        assert_eq!(data[46..82], [0; 36]);
+
+        let account = mock_token_account(key(1), TOKEN_2022, key(3), key(4), 42, None, &[]);
+        assert_eq!(account.data.borrow().len(), 165);
+    }
+
+    #[test]
+    fn mint_extensions_follow_the_padded_base_state() {
+        let extensions = [
+            TokenExtension::TransferFeeConfig {
+                authority: Some(key(5)),
+                withdraw_withheld_authority: None,
+                withheld_amount: 3,
+                maximum_fee: 5000,
+                basis_points: 50,
+            },
+            TokenExtension::MintCloseAuthority { authority: Some(key(6)) },
+            TokenExtension::DefaultAccountState { frozen: true },
+            TokenExtension::NonTransferable,
+            TokenExtension::TransferHook { authority: None, program_id: Some(key(7)) },
+            TokenExtension::MetadataPointer { authority: Some(key(5)), metadata_address: Some(key(1)) },
+            TokenExtension::TokenMetadata {
+                update_authority: Some(key(5)),
+                mint: key(1),
+                name: "Demo".to_string(),
+                symbol: "DMO".to_string(),
+                uri: "u".to_string(),
+                additional_metadata: vec![("k".to_string(), "v".to_string())],
+            },
+        ];
+        let mint = mock_mint(key(1), TOKEN_2022, Some(key(2)), 6, 1, &extensions);
+        let data = mint.data.borrow();
+
+        assert_eq!(data[82..165], [0; 83]);
+        assert_eq!(data[165], 1); // `AccountType::Mint`
+
+        let transfer_fee = [
+            &key(5).to_bytes()[..],
+            &[0; 32],
+            &3u64.to_le_bytes(),
+            &[0; 8],
+            &5000u64.to_le_bytes(),
+            &50u16.to_le_bytes(),
+            &[0; 8],
+            &5000u64.to_le_bytes(),
+            &50u16.to_le_bytes(),
+        ]
+        .concat();
+        assert_eq!(transfer_fee.len(), 108);
+        let metadata = [
+            &key(5).to_bytes()[..],
+            &key(1).to_bytes(),
+            &4u32.to_le_bytes(),
+            b"Demo",
+            &3u32.to_le_bytes(),
+            b"DMO",
+            &1u32.to_le_bytes(),
+            b"u",
+            &1u32.to_le_bytes(),
+            &1u32.to_le_bytes(),
+            b"k",
+            &1u32.to_le_bytes(),
+            b"v",
+        ]
+        .concat();
+        let expected = [
+            entry(1, &transfer_fee),
+            entry(3, &key(6).to_bytes()),
+            entry(6, &[2]),
+            entry(9, &[]),
+            entry(14, &[[0; 32], key(7).to_bytes()].concat()),
+            entry(18, &[key(5).to_bytes(), key(1).to_bytes()].concat()),
+            entry(19, &metadata),
+        ]
+        .concat();
+        assert_eq!(data[166..], expected[..]);
+    }
+
+    #[test]
+    fn account_extensions_follow_the_base_state() {
+        let extensions = [
+            TokenExtension::ImmutableOwner,
+            TokenExtension::TransferFeeAmount { withheld_amount: 9 },
+            TokenExtension::MemoTransfer { require_incoming_transfer_memos: true },
+            TokenExtension::CpiGuard { lock_cpi: false },
+            TokenExtension::DefaultAccountState { frozen: false },
+            TokenExtension::PermanentDelegate { delegate: None },
+        ];
+        let account = mock_token_account(key(1), TOKEN_2022, key(3), key(4), 42, Some(key(8)), &extensions);
+        let data = account.data.borrow();
+
+        assert_eq!(data[64..72], 42u64.to_le_bytes());
+        assert_eq!(data[72..76], 1u32.to_le_bytes());
+        assert_eq!(data[76..108], key(8).to_bytes());
+        assert_eq!(data[121..129], 42u64.to_le_bytes()); // delegated_amount
+        assert_eq!(data[165], 2); // `AccountType::Account`
+
+        let expected = [
+            entry(7, &[]),
+            entry(2, &9u64.to_le_bytes()),
+            entry(8, &[1]),
+            entry(11, &[0]),
+            entry(6, &[1]),
+            entry(12, &[0; 32]),
+        ]
+        .concat();
+        assert_eq!(data[166..], expected[..]);
+    }
+}
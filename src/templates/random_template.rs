@@ -0,0 +1,315 @@
+/*
+    This will be generated as a static module drawing argument values from a seeded RNG (`--random-args`).
+    Values are JSON like `--args-json`, so a failing call can be replayed with the values it printed.
+*/
+
+use anchor_lang::prelude::Pubkey;
+use serde_json::{json, Map, Value};
+
+use crate::codec;
+use crate::int256;
+
+/// SplitMix64: small, deterministic for a given seed and good enough for exploratory inputs
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`
+    pub fn below(&mut self, n: u64) -> u64 {
+        if n == 0 { 0 } else { self.next_u64() % n }
+    }
+}
+
+/// A seed for when none was given, printed so the run can be repeated
+pub fn fresh_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+// Half of the integers are a boundary (min, max, zero, one), the rest uniform over the whole range
+fn integer(rng: &mut Rng, bits: u32, signed: bool) -> Value {
+    let mask = if bits >= 128 { u128::MAX } else { (1u128 << bits) - 1 };
+    let raw = match rng.below(8) {
+        0 => 0,
+        1 => 1,
+        2 => if signed { mask >> 1 } else { mask },
+        3 => if signed { (mask >> 1) + 1 } else { 0 }, // the bit pattern of MIN
+        _ => ((rng.next_u64() as u128) << 64 | rng.next_u64() as u128) & mask,
+    };
+
+    // Sign extended from `bits`, JSON numbers cannot hold every 128-bit value so those are strings
+    let text = if signed {
+        (((raw << (128 - bits)) as i128) >> (128 - bits)).to_string()
+    } else {
+        raw.to_string()
+    };
+    if bits > 64 {
+        json!(text)
+    } else {
+        serde_json::from_str(&text).unwrap_or(json!(text))
+    }
+}
+
+// Same draws as `integer` over the little endian bytes of a 256-bit integer, which has no native type
+fn integer_256(rng: &mut Rng, signed: bool) -> Value {
+    let mut bytes = [0u8; 32];
+    match rng.below(8) {
+        0 => {}
+        1 => bytes[0] = 1,
+        2 => {
+            bytes = [0xff; 32];
+            if signed {
+                bytes[31] = 0x7f;
+            }
+        }
+        3 => {
+            if signed {
+                bytes[31] = 0x80;
+            }
+        }
+        _ => bytes.chunks_mut(8).for_each(|chunk| chunk.copy_from_slice(&rng.next_u64().to_le_bytes())),
+    }
+    json!(int256::decimal_256(&bytes, signed))
+}
+
+fn random_fields(idl: &Value, fields: &Value, rng: &mut Rng) -> Result<Value, String> {
+    let Some(fields) = fields.as_array() else {
+        return Ok(Value::Null);
+    };
+
+    if fields.iter().all(|f| f.get("name").is_some()) {
+        let mut object = Map::new();
+        for field in fields {
+            object.insert(
+                field["name"].as_str().unwrap_or_default().to_string(),
+                random_value(idl, &field["type"], rng)?,
+            );
+        }
+        Ok(Value::Object(object))
+    } else {
+        Ok(Value::Array(fields.iter().map(|ty| random_value(idl, ty, rng)).collect::<Result<_, _>>()?))
+    }
+}
+
+/// A random JSON value of the IDL type `ty`, in the form `codec::encode_value` accepts
+pub fn random_value(idl: &Value, ty: &Value, rng: &mut Rng) -> Result<Value, String> {
+    Ok(match ty {
+        Value::String(s) => match s.as_str() {
+            "bool" => json!(rng.below(2) == 1),
+            "u8" => integer(rng, 8, false),
+            "i8" => integer(rng, 8, true),
+            "u16" => integer(rng, 16, false),
+            "i16" => integer(rng, 16, true),
+            "u32" => integer(rng, 32, false),
+            "i32" => integer(rng, 32, true),
+            "u64" => integer(rng, 64, false),
+            "i64" => integer(rng, 64, true),
+            "u128" => integer(rng, 128, false),
+            "i128" => integer(rng, 128, true),
+            "u256" => integer_256(rng, false),
+            "i256" => integer_256(rng, true),
+            "f32" | "f64" => json!((rng.next_u64() as i64 as f64) / (1u64 << 40) as f64),
+            "string" => {
+                const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 _-";
+                let len = rng.below(33);
+                let text: String = (0..len).map(|_| CHARS[rng.below(CHARS.len() as u64) as usize] as char).collect();
+                json!(text)
+            }
+            "pubkey" => {
+                let mut bytes = [0u8; 32];
+                bytes.iter_mut().for_each(|b| *b = rng.next_u64() as u8);
+                json!(Pubkey::new_from_array(bytes).to_string())
+            }
+            "bytes" => {
+                let len = rng.below(33);
+                json!((0..len).map(|_| rng.next_u64() as u8).collect::<Vec<_>>())
+            }
+            other => return Err(format!("no random value for type `{other}`")),
+        },
+        Value::Object(o) => {
+            if let Some(inner) = o.get("option") {
+                if rng.below(2) == 0 { Value::Null } else { random_value(idl, inner, rng)? }
+            } else if let Some(inner) = o.get("vec") {
+                let len = rng.below(9);
+                Value::Array((0..len).map(|_| random_value(idl, inner, rng)).collect::<Result<_, _>>()?)
+            } else if let Some(array) = o.get("array") {
+                let len = array[1].as_u64().ok_or("generic array lengths are not supported")?;
+                Value::Array((0..len).map(|_| random_value(idl, &array[0], rng)).collect::<Result<_, _>>()?)
+            } else if let Some(defined) = o.get("defined") {
+                let name = defined["name"].as_str().or(defined.as_str()).unwrap_or_default();
+                let type_def = codec::instantiate(codec::find_type(idl, name)?, defined);
+                let ty = &type_def["type"];
+                match ty["kind"].as_str() {
+                    Some("struct") => random_fields(idl, &ty["fields"], rng)?,
+                    Some("enum") => {
+                        let variants = ty["variants"].as_array().ok_or("enum without variants")?;
+                        let variant = &variants[rng.below(variants.len() as u64) as usize];
+                        let name = variant["name"].as_str().ok_or("enum variant without a name")?;
+                        if variant["fields"].is_null() {
+                            json!(name)
+                        } else {
+                            json!({ name: random_fields(idl, &variant["fields"], rng)? })
+                        }
+                    }
+                    Some("type") => random_value(idl, &ty["alias"], rng)?,
+                    _ => return Err(format!("unsupported type definition {name}")),
+                }
+            } else {
+                return Err(format!("no random value for type {}", codec::type_name(ty)));
+            }
+        }
+        _ => return Err(format!("malformed IDL type {ty}")),
+    })
+}
+
+/// Random values of every argument of the instruction `ix`, as an `argument -> value` object
+pub fn random_args(idl: &Value, ix: &Value, rng: &mut Rng) -> Result<Value, String> {
+    let mut values = Map::new();
+    for (arg, ty) in codec::instruction_args(ix) {
+        let value = random_value(idl, &ty, rng).map_err(|e| format!("argument `{arg}`: {e}"))?;
+        values.insert(arg, value);
+    }
+    Ok(Value::Object(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Debug;
+    use std::str::FromStr;
+
+    fn draws(ty: Value, seed: u64) -> Vec<Value> {
+        let mut rng = Rng::new(seed);
+        (0..2000).map(|_| random_value(&json!({}), &ty, &mut rng).unwrap()).collect()
+    }
+
+    #[test]
+    fn the_same_seed_draws_the_same_values() {
+        let ty = json!({ "vec": { "option": "i64" } });
+        assert_eq!(draws(ty.clone(), 42), draws(ty.clone(), 42));
+        assert_ne!(draws(ty.clone(), 42), draws(ty, 43));
+    }
+
+    #[test]
+    fn integers_stay_in_range_and_hit_their_bounds() {
+        fn check<T: FromStr + PartialEq + Debug>(ty: &str, min: T, max: T)
+        where
+            T::Err: Debug,
+        {
+            let values = draws(json!(ty), 7);
+            // JSON numbers up to 64 bits, strings beyond
+            let wide = ty.ends_with("128");
+            assert!(values.iter().all(|v| if wide { v.is_string() } else { v.is_number() }), "{ty}");
+
+            let values: Vec<T> = values
+                .iter()
+                .map(|v| v.as_str().map(str::to_string).unwrap_or(v.to_string()).parse().unwrap())
+                .collect();
+            assert!(values.contains(&min) && values.contains(&max), "{ty}: {min:?} and {max:?} are never drawn");
+        }
+
+        check("u8", u8::MIN, u8::MAX);
+        check("i8", i8::MIN, i8::MAX);
+        check("u16", u16::MIN, u16::MAX);
+        check("i16", i16::MIN, i16::MAX);
+        check("u32", u32::MIN, u32::MAX);
+        check("i32", i32::MIN, i32::MAX);
+        check("u64", u64::MIN, u64::MAX);
+        check("i64", i64::MIN, i64::MAX);
+        check("u128", u128::MIN, u128::MAX);
+        check("i128", i128::MIN, i128::MAX);
+    }
+
+    #[test]
+    fn integers_of_256_bits_cover_the_whole_range() {
+        for (ty, signed) in [("u256", false), ("i256", true)] {
+            let values: Vec<[u8; 32]> = draws(json!(ty), 7)
+                .iter()
+                .map(|v| int256::le_bytes_256(v.as_str().unwrap(), signed).unwrap())
+                .collect();
+
+            let (mut min, mut max) = ([0u8; 32], [0xff; 32]);
+            if signed {
+                min[31] = 0x80;
+                max[31] = 0x7f;
+            }
+            assert!(values.contains(&min) && values.contains(&max), "{ty}: MIN and MAX are never drawn");
+            // Beyond what a 128-bit integer (sign extended) holds
+            assert!(values.iter().any(|v| v[16..] != [0; 16] && v[16..] != [0xff; 16]), "{ty}: only 128-bit values are drawn");
+        }
+    }
+
+    #[test]
+    fn sizes_stay_within_their_limits() {
+        let strings = draws(json!("string"), 1);
+        assert!(strings.iter().all(|v| v.as_str().unwrap().len() <= 32));
+        let bytes = draws(json!("bytes"), 1);
+        assert!(bytes.iter().all(|v| v.as_array().unwrap().len() <= 32));
+        let vecs = draws(json!({ "vec": "u8" }), 1);
+        assert!(vecs.iter().all(|v| v.as_array().unwrap().len() <= 8));
+        assert!(vecs.iter().any(|v| v.as_array().unwrap().is_empty()));
+
+        let options = draws(json!({ "option": "bool" }), 1);
+        assert!(options.iter().any(Value::is_null) && options.iter().any(Value::is_boolean));
+        let arrays = draws(json!({ "array": ["u16", 3] }), 1);
+        assert!(arrays.iter().all(|v| v.as_array().unwrap().len() == 3));
+        let keys = draws(json!("pubkey"), 1);
+        assert!(keys.iter().all(|v| Pubkey::from_str(v.as_str().unwrap()).is_ok()));
+    }
+
+    #[test]
+    fn drawn_arguments_always_encode() {
+        let idl = json!({
+            "instructions": [{
+                "name": "configure",
+                "args": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "delta", "type": "i256" },
+                    { "name": "ratio", "type": "f64" },
+                    { "name": "config", "type": { "defined": { "name": "Config" } } },
+                    { "name": "memo", "type": { "option": "bytes" } },
+                    { "name": "owners", "type": { "vec": "pubkey" } },
+                    { "name": "label", "type": { "defined": { "name": "Label" } } }
+                ]
+            }],
+            "types": [
+                {
+                    "name": "Config",
+                    "type": { "kind": "struct", "fields": [
+                        { "name": "fee_bps", "type": "u16" },
+                        { "name": "mode", "type": { "defined": { "name": "Mode" } } }
+                    ] }
+                },
+                {
+                    "name": "Mode",
+                    "type": { "kind": "enum", "variants": [
+                        { "name": "Off" },
+                        { "name": "Fixed", "fields": [{ "name": "rate", "type": "u8" }] },
+                        { "name": "Pair", "fields": ["i8", "u128"] }
+                    ] }
+                },
+                { "name": "Label", "type": { "kind": "type", "alias": "string" } }
+            ]
+        });
+        let ix = codec::instruction(&idl, "configure").unwrap();
+
+        let mut rng = Rng::new(3);
+        for _ in 0..500 {
+            let values = random_args(&idl, ix, &mut rng).unwrap();
+            codec::encode_args(&idl, ix, &values).unwrap_or_else(|e| panic!("{values}: {e}"));
+        }
+    }
+}
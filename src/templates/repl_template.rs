@@ -0,0 +1,212 @@
+/*
+    This will be generated as a static interactive mode (`--repl`): instructions are called one by one with argument
+    values typed at the prompt, and the account fixtures keep their state between calls.
+*/
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, IsTerminal, Write};
+
+use crate::codec;
+use crate::mock::{fixture_names, keep_fixture_state, set_fixture_overrides, snapshot_fixtures, FixtureSnapshot};
+use crate::runner::Instruction;
+
+const HELP: &str = "Commands:
+    list                 List the instructions and their arguments
+    call <ix> | <ix>     Call an instruction, asking for every argument (unless given with --args-json) and account
+    accounts             List the account fixtures of this session
+    help                 Show this help
+    quit                 Leave the REPL
+
+At an argument prompt press enter for the default or type a JSON value (plain text is taken as a string).
+At an account prompt press enter to keep the shown fixture or type the name of another fixture to use instead.";
+
+/// `arg_values` are the encoded `--args-json` values, the instructions they are given for do not prompt for arguments
+pub fn start(instructions: &[Instruction], arg_values: &HashMap<&'static str, Vec<u8>>) {
+    let idl = codec::idl();
+    keep_fixture_state(true);
+
+    println!("anchor-lldb REPL, type `help` for the commands");
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        let Some(line) = prompt(&mut lines, "> ") else {
+            return;
+        };
+        let mut words = line.split_whitespace();
+
+        match (words.next(), words.next()) {
+            (None, _) => {}
+            (Some("quit" | "exit" | "q"), _) => return,
+            (Some("help" | "h" | "?"), _) => println!("{HELP}"),
+            (Some("list" | "ls"), _) => list(&idl, instructions),
+            (Some("accounts" | "fixtures"), _) => print_fixtures(),
+            (Some("call"), Some(name)) | (Some(name), None) => {
+                match instructions.iter().find(|(ix_name, _)| *ix_name == name) {
+                    Some(instruction) => call(&idl, instruction, arg_values.get(instruction.0), &mut lines),
+                    None => println!("Unknown instruction or command `{name}`, type `help`"),
+                }
+            }
+            _ => println!("Unknown command `{line}`, type `help`"),
+        }
+    }
+}
+
+fn prompt(lines: &mut impl Iterator<Item = std::io::Result<String>>, text: &str) -> Option<String> {
+    // When driven by another program (e.g. `anchor-lldb tui`) the prompts would only clutter the output
+    if std::io::stdin().is_terminal() {
+        print!("{text}");
+        std::io::stdout().flush().ok();
+    }
+    lines.next()?.ok().map(|line| line.trim().to_string())
+}
+
+fn list(idl: &Value, instructions: &[Instruction]) {
+    for (name, _) in instructions {
+        let args = codec::instruction(idl, name)
+            .map(codec::instruction_args)
+            .unwrap_or_default()
+            .iter()
+            .map(|(arg, ty)| format!("{arg}: {}", codec::type_name(ty)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("  {name}({args})");
+    }
+}
+
+fn print_fixtures() {
+    let snapshot = snapshot_fixtures();
+    if snapshot.is_empty() {
+        println!("No fixtures yet, they are created by the first instruction using them");
+    }
+    for (name, (lamports, owner, data)) in snapshot {
+        println!("  {name}: {lamports} lamports, owner {owner}, {} bytes of data", data.len());
+    }
+}
+
+fn call(
+    idl: &Value,
+    (name, call_ix): &Instruction,
+    given_args: Option<&Vec<u8>>,
+    lines: &mut impl Iterator<Item = std::io::Result<String>>,
+) {
+    let Some(ix) = codec::instruction(idl, name) else {
+        println!("`{name}` is not in the embedded IDL");
+        return;
+    };
+
+    let data = match given_args {
+        Some(data) => data.clone(),
+        None => match prompt_args(idl, ix, lines) {
+            Some(data) => data,
+            None => return,
+        },
+    };
+
+    let existing = fixture_names();
+    let mut overrides = HashMap::new();
+    for account in codec::instruction_accounts(ix) {
+        let Some(input) = prompt(lines, &format!("  account {account} [{account}]: ")) else {
+            return;
+        };
+        if !input.is_empty() && input != account {
+            if !existing.contains(&input) {
+                println!("  no fixture named `{input}` yet, a new one is created");
+            }
+            overrides.insert(account, input);
+        }
+    }
+
+    set_fixture_overrides(overrides);
+    let before = snapshot_fixtures();
+
+    let outcome = std::panic::catch_unwind(|| call_ix(Some(&data))).unwrap_or_else(|_| Err("panicked".to_string()));
+
+    let after = snapshot_fixtures();
+    set_fixture_overrides(HashMap::new());
+
+    match outcome {
+        Ok(()) => println!("Result: ok"),
+        Err(e) => println!("Result: failed: {e}"),
+    }
+    print_changes(&before, &after);
+}
+
+// Asks for every argument until its value encodes, `None` when the input ended or an argument has no default
+fn prompt_args(
+    idl: &Value,
+    ix: &Value,
+    lines: &mut impl Iterator<Item = std::io::Result<String>>,
+) -> Option<Vec<u8>> {
+    let mut data = vec![];
+    for (arg, ty) in codec::instruction_args(ix) {
+        let default = match codec::default_value(idl, &ty) {
+            Ok(default) => default,
+            Err(e) => {
+                println!("  {arg}: {e}");
+                return None;
+            }
+        };
+
+        loop {
+            let input = prompt(lines, &format!("  {arg} ({}) [{default}]: ", codec::type_name(&ty)))?;
+            let value = if input.is_empty() { default.clone() } else { codec::parse_input(&input) };
+
+            let mut encoded = vec![];
+            match codec::encode_value(idl, &ty, &value, &mut encoded) {
+                Ok(()) => {
+                    data.extend(encoded);
+                    break;
+                }
+                Err(e) => println!("  invalid value: {e}"),
+            }
+        }
+    }
+    Some(data)
+}
+
+fn print_changes(before: &FixtureSnapshot, after: &FixtureSnapshot) {
+    let mut changes = vec![];
+
+    for (name, (lamports, owner, data)) in after {
+        let Some((old_lamports, old_owner, old_data)) = before.get(name) else {
+            changes.push(format!("  {name}: created ({lamports} lamports, owner {owner}, {} bytes)", data.len()));
+            continue;
+        };
+        if old_lamports != lamports {
+            changes.push(format!("  {name}: lamports {old_lamports} -> {lamports}"));
+        }
+        if old_owner != owner {
+            changes.push(format!("  {name}: owner {old_owner} -> {owner}"));
+        }
+        if old_data != data {
+            changes.push(format!("  {name}: {}", data_diff(old_data, data)));
+        }
+    }
+
+    if changes.is_empty() {
+        println!("No account changed");
+    } else {
+        println!("Changed accounts:");
+        for change in changes {
+            println!("{change}");
+        }
+    }
+}
+
+// Hex of the changed byte range only, accounts are mostly unchanged padding
+fn data_diff(old: &[u8], new: &[u8]) -> String {
+    let len = old.len().max(new.len());
+    let differs = |i: usize| old.get(i) != new.get(i);
+    let start = (0..len).find(|&i| differs(i)).unwrap_or(0);
+    let end = (0..len).rev().find(|&i| differs(i)).map_or(0, |i| i + 1);
+
+    let hex = |bytes: &[u8]| {
+        let range = &bytes[start.min(bytes.len())..end.min(bytes.len())];
+        let shown: String = range.iter().take(64).map(|b| format!("{b:02x}")).collect();
+        if range.len() > 64 { format!("{shown}...") } else { shown }
+    };
+
+    format!("data [{start}..{end}] {} -> {}", hex(old), hex(new))
+}
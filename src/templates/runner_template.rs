@@ -0,0 +1,304 @@
+/*
+    This will be generated as a static runner used by `main.rs` to pick which instructions to execute.
+    Parsing is done by hand so the wrapper crate does not need any extra dependencies.
+*/
+
+pub type Outcome = std::result::Result<(), String>;
+// The call takes the Borsh encoded arguments, `None` uses the generated dummy values
+pub type Instruction = (&'static str, fn(Option<&[u8]>) -> Outcome);
+
+// Prefix of the machine readable result lines printed with --report
+pub const RESULT_MARKER: &str = "::IX_RESULT::";
+
+const USAGE: &str = "Usage: <binary> [--list] [--only a,b] [--skip c] [--repeat N] [--report] [--repl] [--args-json JSON | --args-file PATH]
+               [--random-args [--seed N] [--iterations N]]
+
+    --list          Print the instruction names and exit
+    --only a,b      Run only the given instructions (comma separated, can be repeated)
+    --skip c        Do not run the given instructions (comma separated, can be repeated)
+    --repeat N      Run the selection N times (default: 1)
+    --report        Print a `::IX_RESULT::<name>::<ok|failed: ...>` line after every instruction
+    --repl          Call instructions interactively, account state is kept between calls
+    --args-json J   Argument values instead of the generated ones: '{\"<instruction>\": {\"<argument>\": <value>}}',
+                    the REPL does not ask for the arguments of these instructions
+    --args-file P   Same as --args-json, read from a file
+    --random-args   Draw the argument values from a seeded RNG, failing calls print the seed and their values
+    --seed N        Seed of --random-args (default: from the clock, printed at the start)
+    --iterations N  Random argument values drawn per instruction (default: 1)
+
+Exits with 1 when an instruction failed or panicked, 2 on invalid arguments";
+
+#[derive(Default)]
+pub struct RunOptions {
+    pub list: bool,
+    pub only: Vec<String>,
+    pub skip: Vec<String>,
+    pub repeat: usize,
+    pub report: bool,
+    pub repl: bool,
+    pub args_json: Option<String>,
+    pub random_args: bool,
+    pub seed: Option<u64>,
+    pub iterations: usize,
+}
+
+/// Splits `--flag=value` and `--flag value` forms into the flag and its value
+fn flag_value(
+    arg: &str,
+    name: &str,
+    rest: &mut impl Iterator<Item = String>,
+) -> Option<Result<String, String>> {
+    if arg == name {
+        Some(rest.next().ok_or(format!("{name} expects a value")))
+    } else {
+        arg.strip_prefix(name)
+            .and_then(|v| v.strip_prefix('='))
+            .map(|v| Ok(v.to_string()))
+    }
+}
+
+fn split_names(value: &str) -> impl Iterator<Item = String> + '_ {
+    value
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<RunOptions, String> {
+    let mut options = RunOptions {
+        repeat: 1,
+        iterations: 1,
+        ..Default::default()
+    };
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            println!("{USAGE}");
+            std::process::exit(0);
+        } else if arg == "--list" {
+            options.list = true;
+        } else if arg == "--report" {
+            options.report = true;
+        } else if arg == "--repl" {
+            options.repl = true;
+        } else if arg == "--random-args" {
+            options.random_args = true;
+        } else if let Some(value) = flag_value(&arg, "--only", &mut args) {
+            options.only.extend(split_names(&value?));
+        } else if let Some(value) = flag_value(&arg, "--skip", &mut args) {
+            options.skip.extend(split_names(&value?));
+        } else if let Some(value) = flag_value(&arg, "--args-json", &mut args) {
+            options.args_json = Some(value?);
+        } else if let Some(value) = flag_value(&arg, "--args-file", &mut args) {
+            let path = value?;
+            let json = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read --args-file `{path}`: {e}"))?;
+            options.args_json = Some(json);
+        } else if let Some(value) = flag_value(&arg, "--repeat", &mut args) {
+            let value = value?;
+            options.repeat = value
+                .parse()
+                .map_err(|_| format!("--repeat expects a number, got `{value}`"))?;
+        } else if let Some(value) = flag_value(&arg, "--seed", &mut args) {
+            let value = value?;
+            options.seed = Some(value.parse().map_err(|_| format!("--seed expects a number, got `{value}`"))?);
+        } else if let Some(value) = flag_value(&arg, "--iterations", &mut args) {
+            let value = value?;
+            options.iterations = value
+                .parse()
+                .map_err(|_| format!("--iterations expects a number, got `{value}`"))?;
+        } else {
+            return Err(format!("Unknown argument `{arg}`"));
+        }
+    }
+
+    if options.random_args && (options.args_json.is_some() || options.repl) {
+        return Err("--random-args cannot be combined with --args-json, --args-file or --repl".to_string());
+    }
+    if !options.random_args && (options.seed.is_some() || options.iterations != 1) {
+        return Err("--seed and --iterations only apply to --random-args".to_string());
+    }
+
+    Ok(options)
+}
+
+/// Keeps the IDL order of `instructions`, whatever order `--only` was given in
+pub fn select<'a>(
+    instructions: &'a [Instruction],
+    options: &RunOptions,
+) -> Result<Vec<&'a Instruction>, String> {
+    for name in options.only.iter().chain(&options.skip) {
+        if !instructions.iter().any(|(ix_name, _)| ix_name == name) {
+            return Err(format!("Unknown instruction `{name}`, use --list to see the available ones"));
+        }
+    }
+
+    Ok(instructions
+        .iter()
+        .filter(|(name, _)| options.only.is_empty() || options.only.iter().any(|n| n == name))
+        .filter(|(name, _)| !options.skip.iter().any(|n| n == name))
+        .collect())
+}
+
+/// Borsh encodes the `--args-json` values of every instruction they are given for, with the embedded IDL
+pub fn encode_arg_values(
+    instructions: &[Instruction],
+    args_json: &str,
+) -> Result<std::collections::HashMap<&'static str, Vec<u8>>, String> {
+    let values: serde_json::Value =
+        serde_json::from_str(args_json).map_err(|e| format!("Invalid argument values JSON: {e}"))?;
+    let values = values
+        .as_object()
+        .ok_or("The argument values must be an object of instruction names")?;
+    let idl = crate::codec::idl();
+
+    let mut encoded = std::collections::HashMap::new();
+    for (ix_name, ix_values) in values {
+        let (name, _) = instructions
+            .iter()
+            .find(|(name, _)| name == ix_name)
+            .ok_or(format!("Unknown instruction `{ix_name}` in the argument values"))?;
+        let ix = crate::codec::instruction(&idl, name).ok_or(format!("`{name}` is not in the embedded IDL"))?;
+
+        let data = crate::codec::encode_args(&idl, ix, ix_values).map_err(|e| format!("Instruction `{name}`: {e}"))?;
+        encoded.insert(*name, data);
+    }
+
+    Ok(encoded)
+}
+
+pub fn run(instructions: &[Instruction]) {
+    let options = parse_args(std::env::args().skip(1)).unwrap_or_else(|e| exit_with_usage(&e));
+
+    if options.list {
+        for (name, _) in instructions {
+            println!("{name}");
+        }
+        return;
+    }
+
+    let selected = select(instructions, &options).unwrap_or_else(|e| exit_with_usage(&e));
+    let arg_values = match &options.args_json {
+        Some(json) => encode_arg_values(instructions, json).unwrap_or_else(|e| exit_with_usage(&e)),
+        None => Default::default(),
+    };
+
+    if options.repl {
+        let selected: Vec<Instruction> = selected.into_iter().copied().collect();
+        crate::repl::start(&selected, &arg_values);
+        return;
+    }
+
+    if options.random_args {
+        if run_random(&selected, &options) > 0 {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut outcomes = vec![];
+    for round in 1..=options.repeat {
+        if options.repeat > 1 {
+            println!("--- run {round}/{} ---", options.repeat);
+        }
+        for (name, call) in &selected {
+            let args = arg_values.get(name).map(Vec::as_slice);
+            // A panicking handler should not stop the remaining instructions from running
+            let outcome = std::panic::catch_unwind(|| call(args)).unwrap_or_else(|_| Err("panicked".to_string()));
+
+            if options.report {
+                let result = match &outcome {
+                    Ok(()) => "ok".to_string(),
+                    Err(e) => format!("failed: {}", e.replace('\n', " ")),
+                };
+                println!("{RESULT_MARKER}{name}::{result}");
+            }
+            outcomes.push((*name, round, outcome));
+        }
+    }
+
+    // Boundary cases (`<instruction>#<case>`, generated with --matrix) are summarized once everything ran
+    if selected.iter().any(|(name, _)| name.contains('#')) {
+        print_summary(&outcomes, options.repeat > 1);
+    }
+
+    // Scripts and CI tell a failing selection from the exit status
+    if outcomes.iter().any(|(_, _, outcome)| outcome.is_err()) {
+        std::process::exit(1);
+    }
+}
+
+/// `--random-args`: every iteration draws fresh values for each selected instruction from one seeded RNG,
+/// so the same seed, selection and iteration count reproduce the same calls. Returns the number of failed calls
+fn run_random(selected: &[&Instruction], options: &RunOptions) -> usize {
+    let idl = crate::codec::idl();
+    let seed = options.seed.unwrap_or_else(crate::random::fresh_seed);
+    let mut rng = crate::random::Rng::new(seed);
+    println!("[RANDOM] seed {seed}, {} iteration(s)", options.iterations);
+
+    let mut calls = 0;
+    let mut failed = 0;
+    for iteration in 1..=options.iterations {
+        for (name, call) in selected {
+            // Boundary cases (`--matrix`) have fixed values
+            if name.contains('#') {
+                continue;
+            }
+            let Some(ix) = crate::codec::instruction(&idl, name) else {
+                continue;
+            };
+
+            let values = crate::random::random_args(&idl, ix, &mut rng);
+            let outcome = values
+                .as_ref()
+                .map_err(|e| e.clone())
+                .and_then(|values| crate::codec::encode_args(&idl, ix, values))
+                .and_then(|data| {
+                    std::panic::catch_unwind(|| call(Some(&data))).unwrap_or_else(|_| Err("panicked".to_string()))
+                });
+            calls += 1;
+
+            if options.report {
+                let result = match &outcome {
+                    Ok(()) => "ok".to_string(),
+                    Err(e) => format!("failed: {}", e.replace('\n', " ")),
+                };
+                println!("{RESULT_MARKER}{name}::{result}");
+            }
+            if let Err(e) = outcome {
+                failed += 1;
+                println!("[RANDOM] FAIL {name} (seed {seed}, iteration {iteration}): {}", e.replace('\n', " "));
+                if let Ok(values) = values {
+                    let replay = serde_json::json!({ *name: values });
+                    println!("[RANDOM]   values: {values}");
+                    println!("[RANDOM]   replay: --only {name} --args-json '{replay}'");
+                }
+            }
+        }
+    }
+
+    println!("\n[RANDOM] seed {seed}: {} passed, {failed} failed", calls - failed);
+    failed
+}
+
+fn print_summary(outcomes: &[(&str, usize, Outcome)], show_round: bool) {
+    let label = |name: &str, round: usize| if show_round { format!("{name} (run {round})") } else { name.to_string() };
+    let width = outcomes.iter().map(|(name, round, _)| label(name, *round).len()).max().unwrap_or(0).max(4);
+
+    println!("\n{:<width$}  RESULT", "CASE");
+    for (name, round, outcome) in outcomes {
+        let result = match outcome {
+            Ok(()) => "pass".to_string(),
+            Err(e) => format!("FAIL: {}", e.replace('\n', " ")),
+        };
+        println!("{:<width$}  {result}", label(name, *round));
+    }
+
+    let failed = outcomes.iter().filter(|(_, _, outcome)| outcome.is_err()).count();
+    println!("\n{} passed, {} failed", outcomes.len() - failed, failed);
+}
+
+fn exit_with_usage(error: &str) -> ! {
+    eprintln!("{error}\n\n{USAGE}");
+    std::process::exit(2);
+}
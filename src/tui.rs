@@ -0,0 +1,365 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anchor_idl::{Idl, IdlConst, IdlInstruction, IdlTypeDef};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+
+use crate::debugger::{self, LldbOptions};
+use crate::generator::accounts::InstructionAcountCode;
+use crate::generator::bumps::InstructionBumpsCode;
+use crate::generator::config::MockSettings;
+use crate::utils::{flatten_accounts, idl_type_to_string};
+
+/*
+Terminal UI over an already built debug binary: browse the instructions, edit their arguments and run or debug them.
+
+Runs go through the binary's `--repl` mode with the edited values as `--args-json`, which the binary checks against
+its embedded IDL, so nothing is regenerated.
+*/
+
+#[derive(PartialEq)]
+enum Focus {
+    Instructions,
+    Arguments,
+}
+
+struct ArgumentInput {
+    name: String,
+    ty: String,
+    value: String, // empty means the default value of the REPL
+}
+
+struct InstructionView {
+    name: String,
+    accounts: Vec<String>,
+    seeds: Vec<String>,
+    account_count: usize,
+    args: Vec<ArgumentInput>,
+}
+
+#[derive(Default)]
+struct LastRun {
+    instruction: String,
+    logs: Vec<String>,
+    result: Option<String>,
+    account_changes: Vec<String>,
+}
+
+struct App<'a> {
+    bin_path: &'a Path,
+    program_mod: &'a str,
+    instructions: Vec<InstructionView>,
+    selected: ListState,
+    selected_arg: usize,
+    focus: Focus,
+    last_run: LastRun,
+}
+
+impl InstructionView {
+    fn new(ix: &IdlInstruction, types: &[IdlTypeDef], constants: &[IdlConst], account_map: &HashMap<String, String>) -> Result<Self, String> {
+        // Only the struct fields are shown, they do not depend on the mock settings
+        let account_code = InstructionAcountCode::generate_account_code(ix, types, constants, account_map, &MockSettings::default(), false)?;
+        let bumps_code = InstructionBumpsCode::generate_bumps_code(ix, types, constants, account_map)?;
+
+        Ok(InstructionView {
+            name: ix.name.clone(),
+            accounts: account_code.fields,
+            seeds: bumps_code.bump_fields,
+            account_count: flatten_accounts(&ix.accounts).len(),
+            args: ix
+                .args
+                .iter()
+                .map(|arg| ArgumentInput {
+                    name: arg.name.clone(),
+                    ty: idl_type_to_string(&arg.ty),
+                    value: String::new(),
+                })
+                .collect(),
+        })
+    }
+
+    // `--args-json` of the edited values, the empty ones keep their default. Typed like at the REPL prompt: JSON when
+    // it parses, plain text as a string, integers as their digits so the ones beyond 64 bits are not rounded
+    fn args_json(&self) -> String {
+        let values: serde_json::Map<String, Value> = self
+            .args
+            .iter()
+            .filter(|arg| !arg.value.trim().is_empty())
+            .map(|arg| {
+                let input = arg.value.trim();
+                let digits = input.strip_prefix('-').unwrap_or(input);
+                let value = if digits.bytes().all(|d| d.is_ascii_digit()) {
+                    Value::String(input.to_string())
+                } else {
+                    serde_json::from_str(input).unwrap_or_else(|_| Value::String(input.to_string()))
+                };
+                (arg.name.clone(), value)
+            })
+            .collect();
+
+        let mut json = serde_json::Map::new();
+        json.insert(self.name.clone(), Value::Object(values));
+        Value::Object(json).to_string()
+    }
+}
+
+pub fn start(
+    idl: &Idl,
+    account_map: &HashMap<String, String>,
+    bin_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut selected = ListState::default();
+    selected.select(Some(0));
+
+    let mut app = App {
+        bin_path,
+        program_mod: &idl.metadata.name,
+        instructions: idl
+            .instructions
+            .iter()
+            .map(|ix| InstructionView::new(ix, &idl.types, &idl.constants, account_map))
+            .collect::<Result<_, _>>()?,
+        selected,
+        selected_arg: 0,
+        focus: Focus::Instructions,
+        last_run: LastRun::default(),
+    };
+
+    if app.instructions.is_empty() {
+        return Err("The IDL has no instructions".into());
+    }
+
+    let mut terminal = ratatui::init();
+    let result = app.event_loop(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl App<'_> {
+    fn current(&self) -> &InstructionView {
+        &self.instructions[self.selected.selected().unwrap_or(0)]
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<(), Box<dyn std::error::Error>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match (&self.focus, key.code) {
+                (_, KeyCode::Tab) => {
+                    self.focus = match self.focus {
+                        Focus::Instructions if !self.current().args.is_empty() => Focus::Arguments,
+                        _ => Focus::Instructions,
+                    };
+                    self.selected_arg = 0;
+                }
+                (_, KeyCode::Esc) if self.focus == Focus::Arguments => self.focus = Focus::Instructions,
+                (Focus::Instructions, KeyCode::Char('q')) | (_, KeyCode::Esc) => return Ok(()),
+                (Focus::Instructions, KeyCode::Up) => self.selected.select_previous(),
+                (Focus::Instructions, KeyCode::Down)
+                    if self.selected.selected() < Some(self.instructions.len() - 1) =>
+                {
+                    self.selected.select_next()
+                }
+                (Focus::Instructions, KeyCode::Char('r')) | (_, KeyCode::Enter) => self.run_selected()?,
+                (Focus::Instructions, KeyCode::Char('d')) => {
+                    ratatui::restore();
+                    let debug_result = self.debug_selected();
+                    *terminal = ratatui::init();
+                    if let Err(e) = debug_result {
+                        self.last_run = LastRun {
+                            result: Some(format!("failed to start the debugger: {e}")),
+                            ..Default::default()
+                        };
+                    }
+                }
+                (Focus::Arguments, KeyCode::Up) => self.selected_arg = self.selected_arg.saturating_sub(1),
+                (Focus::Arguments, KeyCode::Down) => {
+                    self.selected_arg = (self.selected_arg + 1).min(self.current().args.len() - 1)
+                }
+                (Focus::Arguments, KeyCode::Backspace) => {
+                    let index = self.selected.selected().unwrap_or(0);
+                    self.instructions[index].args[self.selected_arg].value.pop();
+                }
+                (Focus::Arguments, KeyCode::Char(c)) => {
+                    let index = self.selected.selected().unwrap_or(0);
+                    self.instructions[index].args[self.selected_arg].value.push(c);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] = Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(main);
+        let [accounts_area, args_area, run_area] = Layout::vertical([
+            Constraint::Percentage(30),
+            Constraint::Percentage(25),
+            Constraint::Percentage(45),
+        ])
+        .areas(right);
+
+        let focused = |focus: Focus| {
+            if self.focus == focus {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            }
+        };
+
+        let items: Vec<ListItem> = self.instructions.iter().map(|ix| ListItem::new(ix.name.clone())).collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Instructions").border_style(focused(Focus::Instructions)))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, left, &mut self.selected);
+
+        let current = self.current();
+
+        let mut account_lines: Vec<Line> = current.accounts.iter().map(|a| Line::from(a.clone())).collect();
+        if !current.seeds.is_empty() {
+            account_lines.push(Line::from(""));
+            account_lines.push(Line::from("Seeds / bumps:").style(Style::default().add_modifier(Modifier::BOLD)));
+            account_lines.extend(current.seeds.iter().map(|s| Line::from(s.clone())));
+        }
+        frame.render_widget(
+            Paragraph::new(account_lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title("Accounts & seeds")),
+            accounts_area,
+        );
+
+        let arg_lines: Vec<Line> = if current.args.is_empty() {
+            vec![Line::from("(no arguments)")]
+        } else {
+            current
+                .args
+                .iter()
+                .enumerate()
+                .map(|(i, arg)| {
+                    let value = if arg.value.is_empty() { "<default>" } else { arg.value.as_str() };
+                    let line = Line::from(format!("{} ({}): {}", arg.name, arg.ty, value));
+                    if self.focus == Focus::Arguments && i == self.selected_arg {
+                        line.style(Style::default().add_modifier(Modifier::REVERSED))
+                    } else {
+                        line
+                    }
+                })
+                .collect()
+        };
+        frame.render_widget(
+            Paragraph::new(arg_lines).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Arguments (JSON values)")
+                    .border_style(focused(Focus::Arguments)),
+            ),
+            args_area,
+        );
+
+        let mut run_lines = vec![];
+        if let Some(result) = &self.last_run.result {
+            let color = if result == "ok" { Color::Green } else { Color::Red };
+            run_lines.push(Line::from(format!("{}: {}", self.last_run.instruction, result)).style(Style::default().fg(color)));
+        }
+        if !self.last_run.account_changes.is_empty() {
+            run_lines.push(Line::from("Changed accounts:").style(Style::default().add_modifier(Modifier::BOLD)));
+            run_lines.extend(self.last_run.account_changes.iter().map(|c| Line::from(c.clone())));
+        }
+        if !self.last_run.logs.is_empty() {
+            run_lines.push(Line::from("Logs:").style(Style::default().add_modifier(Modifier::BOLD)));
+            run_lines.extend(self.last_run.logs.iter().map(|l| Line::from(l.clone())));
+        }
+        frame.render_widget(
+            Paragraph::new(run_lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title("Last run")),
+            run_area,
+        );
+
+        frame.render_widget(
+            Paragraph::new("↑/↓ select  Tab edit arguments  r/Enter run  d debug in lldb  Esc back  q quit"),
+            footer,
+        );
+    }
+
+    // Drives `--repl` through stdin: the arguments are given with `--args-json`, then enter for every account keeps
+    // the default fixtures
+    fn run_selected(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let current = self.current();
+
+        let mut input = format!("call {}\n", current.name);
+        input.push_str(&"\n".repeat(current.account_count));
+        input.push_str("quit\n");
+
+        let mut child = Command::new(self.bin_path)
+            .args(["--repl", "--only", &current.name, "--args-json", &current.args_json()])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        child.stdin.take().ok_or("Failed to open the debug binary stdin")?.write_all(input.as_bytes())?;
+        let output = child.wait_with_output()?;
+
+        self.last_run = parse_repl_output(&current.name, &String::from_utf8_lossy(&output.stdout), &String::from_utf8_lossy(&output.stderr));
+        Ok(())
+    }
+
+    fn debug_selected(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let current = self.current();
+        let ix_name = &current.name;
+        debugger::launch_lldb(
+            self.bin_path,
+            &LldbOptions {
+                program_mod: self.program_mod,
+                instruction: Some(ix_name),
+                break_in_handler: true,
+                extra_args: &[],
+                program_args: &["--only".to_string(), ix_name.clone(), "--args-json".to_string(), current.args_json()],
+            },
+        )
+    }
+}
+
+fn parse_repl_output(instruction: &str, stdout: &str, stderr: &str) -> LastRun {
+    let mut run = LastRun {
+        instruction: instruction.to_string(),
+        ..Default::default()
+    };
+    let mut in_changes = false;
+
+    for line in stdout.lines() {
+        if let Some(result) = line.strip_prefix("Result: ") {
+            run.result = Some(result.to_string());
+        } else if line == "Changed accounts:" {
+            in_changes = true;
+        } else if in_changes && line.starts_with("  ") {
+            run.account_changes.push(line.trim().to_string());
+        } else if !line.starts_with("Native debug wrapper") && !line.starts_with("anchor-lldb REPL") && line != "No account changed" {
+            in_changes = false;
+            run.logs.push(line.to_string());
+        }
+    }
+    run.logs.extend(stderr.lines().map(str::to_string));
+
+    if run.result.is_none() {
+        run.result = Some("no result, the debug binary exited early (see logs)".to_string());
+    }
+    run
+}
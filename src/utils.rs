@@ -1,7 +1,7 @@
-use anchor_idl::{IdlInstructionAccount, IdlInstructionAccountItem};
+use anchor_idl::{IdlArrayLen, IdlGenericArg, IdlInstructionAccount, IdlInstructionAccountItem, IdlInstructionAccounts, IdlType};
 use regex::Regex;
 use toml_edit::{DocumentMut, Item, Value};
-use std::{fs, path::Path};
+use std::{fs, path::{Path, PathBuf}};
 
 // Just format util fn
 pub fn to_camel_case(s: &str) -> String {
@@ -17,20 +17,86 @@ pub fn to_camel_case(s: &str) -> String {
         .collect::<String>()
 }
 
-// Used to extract the Single account recursively 
-pub fn visit_account_item(item: &IdlInstructionAccountItem) -> Option<&IdlInstructionAccount> {
-    match item {
-        IdlInstructionAccountItem::Single(account) => Some(account),
-        IdlInstructionAccountItem::Composite(group) => {
-            for nested in &group.accounts {
-                // Recursive till reached Single accout of the group
-                if let Some(acc) = visit_account_item(nested) {
-                    return Some(acc);
-                }
-            }
-            None
+// Every Single account of the instruction, nested groups flattened in declaration order
+pub fn flatten_accounts(items: &[IdlInstructionAccountItem]) -> Vec<&IdlInstructionAccount> {
+    account_paths(items).into_iter().map(|(_, account)| account).collect()
+}
+
+// Same as `flatten_accounts` with the path of every account, `group.account` inside composite groups
+pub fn account_paths(items: &[IdlInstructionAccountItem]) -> Vec<(String, &IdlInstructionAccount)> {
+    let mut accounts = vec![];
+    for item in items {
+        match item {
+            IdlInstructionAccountItem::Single(account) => accounts.push((account.name.clone(), account)),
+            IdlInstructionAccountItem::Composite(group) => accounts.extend(
+                account_paths(&group.accounts)
+                    .into_iter()
+                    .map(|(path, account)| (format!("{}.{}", group.name, path), account)),
+            ),
+        }
+    }
+    accounts
+}
+
+// Name of the generated binding of the account at `path`, `group_account` inside composite groups so two groups with
+// the same account names get their own mocks
+pub fn binding_name(path: &str) -> String {
+    path.replace('.', "_")
+}
+
+// Path of the account a seed or constraint of an account in `scope` (the path of its group, empty at the top level)
+// refers to. Anchor writes these relative to the `Accounts` struct declaring them, the first segment is a field of it
+// and the others are fields of its data, e.g. `counter.authority`
+pub fn scoped_path(scope: &str, reference: &str) -> String {
+    let field = reference.split('.').next().unwrap_or(reference);
+    if scope.is_empty() {
+        field.to_string()
+    } else {
+        format!("{}.{}", scope, field)
+    }
+}
+
+// Every composite group of the instruction, nested ones included
+pub fn composite_groups(items: &[IdlInstructionAccountItem]) -> Vec<&IdlInstructionAccounts> {
+    let mut groups = vec![];
+    for item in items {
+        if let IdlInstructionAccountItem::Composite(group) = item {
+            groups.push(group);
+            groups.extend(composite_groups(&group.accounts));
         }
     }
+    groups
+}
+
+/// Rust-like name of an IDL type, unlike `anchor_idl::ty_to_rust_type` it never panics
+pub fn idl_type_to_string(ty: &IdlType) -> String {
+    match ty {
+        IdlType::Option(inner) => format!("Option<{}>", idl_type_to_string(inner)),
+        IdlType::Vec(inner) => format!("Vec<{}>", idl_type_to_string(inner)),
+        IdlType::Array(inner, IdlArrayLen::Value(n)) => format!("[{}; {}]", idl_type_to_string(inner), n),
+        IdlType::Array(inner, IdlArrayLen::Generic(n)) => format!("[{}; {}]", idl_type_to_string(inner), n),
+        IdlType::Defined { name, generics } if generics.is_empty() => name.clone(),
+        IdlType::Defined { name, generics } => {
+            let generics = generics
+                .iter()
+                .map(|g| match g {
+                    IdlGenericArg::Type { ty } => idl_type_to_string(ty),
+                    IdlGenericArg::Const { value } => value.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{name}<{generics}>")
+        }
+        IdlType::Generic(name) => name.clone(),
+        IdlType::Bytes => "Vec<u8>".to_string(),
+        IdlType::String => "String".to_string(),
+        IdlType::Pubkey => "Pubkey".to_string(),
+        // The primitives serialize to their Rust name
+        other => serde_json::to_value(other)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_else(|| format!("{:?}", other)),
+    }
 }
 
 // Used to derive tha paths when using `anchor-lldb generate` instead of user passing the paths manually
@@ -38,7 +104,7 @@ pub fn infer_paths(package: &str) -> Result<(String, String), Box<dyn std::error
     let dir = std::env::current_dir()?;
 
     let program_path = dir.join(format!("programs/{}", package));
-    let cargo_path = program_path.join(format!("Cargo.toml"));
+    let cargo_path = program_path.join("Cargo.toml");
     let lib_rs_path = program_path.join("src/lib.rs");
 
     if !cargo_path.exists() {
@@ -99,4 +165,11 @@ pub fn cli_error<E: std::fmt::Display>(e: E) -> ! {
 /// Replaces '-' with '_', just like Cargo does internally.
 pub fn binary_name_from_package(package: &str) -> String {
     package.replace("-", "_")
-}
\ No newline at end of file
+}
+/// Looks up an executable by name in every directory listed in `PATH`.
+pub fn find_on_path(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+use walkdir::WalkDir;
+
+/*
+Polling based watch mode: waits for the program sources, the IDL or `Anchor.toml` to change, and compares the
+per-instruction results of consecutive runs of the debug binary.
+*/
+
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+// Editors often write a file several times on save, so wait until nothing changed for this long
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+// Must match `RESULT_MARKER` in `templates/runner_template.rs`
+const RESULT_MARKER: &str = "::IX_RESULT::";
+
+/// Instruction name -> `ok` / `failed: ...`, in execution order
+pub type RunResults = Vec<(String, String)>;
+
+/// Modification time of every watched file
+pub type Snapshot = HashMap<PathBuf, SystemTime>;
+
+pub fn watch_paths(program_crate_path: &str, idl_path: &str) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    Ok(vec![
+        Path::new(program_crate_path).join("src"),
+        PathBuf::from(idl_path),
+        std::env::current_dir()?.join("Anchor.toml"),
+    ])
+}
+
+pub fn snapshot(paths: &[PathBuf]) -> Snapshot {
+    paths
+        .iter()
+        .flat_map(|path| WalkDir::new(path).into_iter().filter_map(Result::ok))
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((entry.into_path(), modified))
+        })
+        .collect()
+}
+
+/// Blocks until one of the watched paths differs from `initial` and then stayed untouched for the debounce period.
+/// `initial` is taken before the run it follows, so a change saved during the run is not missed
+pub fn wait_for_change(paths: &[PathBuf], initial: &Snapshot) {
+    let mut last = loop {
+        let current = snapshot(paths);
+        if &current != initial {
+            break current;
+        }
+        sleep(POLL_INTERVAL);
+    };
+
+    loop {
+        sleep(DEBOUNCE);
+        let current = snapshot(paths);
+        if current == last {
+            return;
+        }
+        last = current;
+    }
+}
+
+/// Runs the binary with `--report`, passing its regular output through and collecting the result lines
+pub fn run_and_collect(bin_path: &Path, bin_args: &[String]) -> Result<RunResults, Box<dyn std::error::Error>> {
+    let mut child = Command::new(bin_path)
+        .args(bin_args)
+        .arg("--report")
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    let stdout = child.stdout.take().ok_or("Failed to capture the debug binary output")?;
+    let mut results: RunResults = vec![];
+
+    for line in BufReader::new(stdout).lines() {
+        let line = line?;
+        match line.strip_prefix(RESULT_MARKER).and_then(|rest| rest.split_once("::")) {
+            Some((name, result)) => {
+                // With --repeat the last run of an instruction wins
+                results.retain(|(n, _)| n != name);
+                results.push((name.to_string(), result.to_string()));
+            }
+            None => println!("{line}"),
+        }
+    }
+
+    // 1 is a failed instruction, which the results already tell
+    let status = child.wait()?;
+    if !status.success() && status.code() != Some(1) {
+        return Err(format!("Execution of the debug wrapper binary failed with {}", status).into());
+    }
+
+    Ok(results)
+}
+
+/// Describes every instruction whose result differs from the previous run
+pub fn diff_results(previous: &RunResults, current: &RunResults) -> Vec<String> {
+    current
+        .iter()
+        .filter_map(|(name, result)| {
+            match previous.iter().find(|(n, _)| n == name) {
+                Some((_, before)) if before == result => None,
+                Some((_, before)) => Some(format!("{name}: {before} -> {result}")),
+                None => Some(format!("{name}: (not run) -> {result}")),
+            }
+        })
+        .collect()
+}